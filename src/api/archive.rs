@@ -0,0 +1,120 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use json;
+
+use crate::filesystem::{self, get_pkit_cache_dir};
+
+use super::cache::cache_key;
+use super::checksum::{Checksum, Hasher};
+use super::Version;
+
+const ENTRY_FILE_NAME: &str = "entry.json";
+
+/// A downloaded archive kept in the cache directory for later installs.
+pub struct CachedArchive {
+    pub path: PathBuf,
+    pub url: String,
+    pub checksum: Option<Checksum>,
+    pub verified: bool,
+    pub size: u64,
+    pub cached_at: u64,
+}
+
+pub fn get_archive_cache_dir() -> io::Result<PathBuf> {
+    Ok(get_pkit_cache_dir()?.join("archives"))
+}
+
+/// Archives with a known digest are keyed by it, so the same file is reused whichever URL
+/// or mirror it came from; everything else is keyed by its URL.
+fn entry_dir(url: &str, checksum: Option<&Checksum>) -> io::Result<PathBuf> {
+    let key = match checksum {
+        Some(checksum) => format!("{}-{}", checksum.algorithm.name(), checksum.value),
+        None => format!("url-{}", cache_key(url)),
+    };
+    Ok(get_archive_cache_dir()?.join(key))
+}
+
+fn file_name(url: &str) -> &str {
+    url.split('/').next_back().filter(|name| !name.is_empty()).unwrap_or("download.tmp")
+}
+
+/// Where a fresh download of `version` should be written.
+pub fn get_archive_path(version: &Version, checksum: Option<&Checksum>) -> io::Result<PathBuf> {
+    Ok(entry_dir(&version.url, checksum)?.join(file_name(&version.url)))
+}
+
+fn read_entry(dir: &Path) -> Option<CachedArchive> {
+    let json_data = json::parse(&filesystem::read(&dir.join(ENTRY_FILE_NAME)).ok()?).ok()?;
+    let path = dir.join(json_data["file_name"].as_str()?);
+    let size = fs::metadata(&path).ok()?.len();
+
+    Some(CachedArchive {
+        path,
+        url: json_data["url"].as_str()?.to_string(),
+        checksum: json_data["checksum"].as_str().and_then(Checksum::parse),
+        verified: json_data["verified"].as_bool().unwrap_or(false),
+        size,
+        cached_at: json_data["cached_at"].as_u64().unwrap_or(0),
+    })
+}
+
+/// Records the archive just downloaded to `path` so later installs can reuse it.
+pub fn record(path: &Path, url: &str, checksum: &Checksum, verified: bool) -> io::Result<()> {
+    let dir = path.parent().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid archive path"))?;
+    let cached_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let mut json_data = json::JsonValue::new_object();
+    json_data["url"] = url.into();
+    json_data["file_name"] = path.file_name().and_then(|name| name.to_str()).unwrap_or("").into();
+    json_data["checksum"] = checksum.to_string().into();
+    json_data["verified"] = verified.into();
+    json_data["cached_at"] = cached_at.into();
+
+    filesystem::write(&dir.join(ENTRY_FILE_NAME), &json_data.pretty(2))
+}
+
+fn hash_file(path: &Path, checksum: &Checksum) -> io::Result<Checksum> {
+    let mut hasher = Hasher::new(checksum.algorithm);
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize())
+}
+
+/// Looks for a usable cached copy of `version`.
+///
+/// With an expected digest the entry for that digest is re-hashed before it is trusted and
+/// dropped if it no longer matches. Without one, an archive previously fetched from the
+/// same URL is used, which is what makes offline reinstalls possible.
+pub fn find(version: &Version, expected: Option<&Checksum>) -> Option<CachedArchive> {
+    if let Some(expected) = expected {
+        let dir = entry_dir(&version.url, Some(expected)).ok()?;
+        let archive = read_entry(&dir)?;
+        if hash_file(&archive.path, expected).ok().as_ref() == Some(expected) {
+            return Some(archive);
+        }
+        let _ = fs::remove_dir_all(&dir);
+        return None;
+    }
+
+    list().into_iter().find(|archive| archive.url == version.url)
+}
+
+/// Every archive currently in the cache.
+pub fn list() -> Vec<CachedArchive> {
+    let Ok(dir) = get_archive_cache_dir() else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| read_entry(&entry.path()))
+        .collect()
+}
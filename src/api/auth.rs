@@ -0,0 +1,179 @@
+use std::env;
+use std::fmt;
+use std::path::PathBuf;
+
+use reqwest::{RequestBuilder, Url};
+
+use crate::filesystem::{self, config::Registry};
+
+use super::registry::get_registries;
+
+/// Overrides the location of the netrc file, like curl and git do.
+pub const NETRC_ENV_VAR: &str = "NETRC";
+
+/// Credentials sent with requests to a private registry.
+#[derive(Clone, PartialEq)]
+pub enum Credentials {
+    Bearer(String),
+    Basic { username: String, password: Option<String> },
+}
+
+/// Never shows the secret itself, so credentials can't leak into logs by accident.
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Credentials::Bearer(_) => write!(f, "Bearer(<redacted>)"),
+            Credentials::Basic { username, .. } => write!(f, "Basic({}, <redacted>)", username),
+        }
+    }
+}
+
+impl Credentials {
+    fn apply(&self, request: RequestBuilder) -> RequestBuilder {
+        match self {
+            Credentials::Bearer(token) => request.bearer_auth(token),
+            Credentials::Basic { username, password } => request.basic_auth(username, password.as_ref()),
+        }
+    }
+}
+
+/// Removes any `user:password@` part from a URL before it is shown to the user.
+pub fn redact_url(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let authority_end = rest.find('/').unwrap_or(rest.len());
+    match rest[..authority_end].rfind('@') {
+        Some(at) => format!("{}://{}", scheme, &rest[at + 1..]),
+        None => url.to_string(),
+    }
+}
+
+/// Whether `url` is served by `registry`: same scheme, host and port, and at or below the
+/// registry's path, which covers archives the registry hosts itself.
+///
+/// The path must match on a `/` boundary, so `/repo` doesn't cover `/repository`.
+fn is_served_by(registry: &Registry, url: &Url) -> bool {
+    let Ok(base) = Url::parse(&registry.url) else {
+        return false;
+    };
+    if base.scheme() != url.scheme()
+        || base.host_str() != url.host_str()
+        || base.port_or_known_default() != url.port_or_known_default()
+    {
+        return false;
+    }
+
+    let prefix = base.path().trim_end_matches('/');
+    match url.path().strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+/// Credentials configured for a registry: `token_env` beats `token`, which beats a username.
+fn registry_credentials(registry: &Registry) -> Option<Credentials> {
+    let auth = &registry.auth;
+
+    if let Some(token) = auth.token_env.as_ref().and_then(|name| env::var(name).ok()).filter(|t| !t.is_empty()) {
+        return Some(Credentials::Bearer(token));
+    }
+    if let Some(token) = &auth.token {
+        return Some(Credentials::Bearer(token.clone()));
+    }
+    auth.username.as_ref().map(|username| Credentials::Basic {
+        username: username.clone(),
+        password: auth.password.clone(),
+    })
+}
+
+fn get_netrc_path() -> Option<PathBuf> {
+    if let Some(path) = env::var_os(NETRC_ENV_VAR) {
+        return Some(PathBuf::from(path));
+    }
+    let home = home::home_dir()?;
+    [".netrc", "_netrc"].iter().map(|name| home.join(name)).find(|path| path.exists())
+}
+
+/// Looks up `host` in netrc content, falling back to its `default` entry.
+fn find_in_netrc(content: &str, host: &str) -> Option<Credentials> {
+    let mut tokens = content.split_whitespace();
+    let mut entries: Vec<(Option<String>, Option<String>, Option<String>)> = Vec::new();
+
+    while let Some(token) = tokens.next() {
+        match token {
+            "machine" => entries.push((tokens.next().map(String::from), None, None)),
+            "default" => entries.push((None, None, None)),
+            "login" | "password" => {
+                let value = tokens.next().map(String::from);
+                if let Some(entry) = entries.last_mut() {
+                    if token == "login" { entry.1 = value } else { entry.2 = value }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    entries
+        .iter()
+        .find(|(machine, _, _)| machine.as_deref() == Some(host))
+        .or_else(|| entries.iter().find(|(machine, _, _)| machine.is_none()))
+        .and_then(|(_, login, password)| {
+            login.as_ref().map(|username| Credentials::Basic { username: username.clone(), password: password.clone() })
+        })
+}
+
+/// Finds the credentials to send with a request to `url`, if any.
+///
+/// Registries configured in pkit.json are checked first, then `~/.netrc` (or `$NETRC`)
+/// by host name. Requests to other hosts are sent without credentials.
+pub fn get_credentials(url: &str) -> Option<Credentials> {
+    let parsed = Url::parse(url).ok()?;
+
+    let from_registry = get_registries()
+        .iter()
+        .filter(|registry| is_served_by(registry, &parsed))
+        .find_map(registry_credentials);
+    if from_registry.is_some() {
+        return from_registry;
+    }
+
+    let content = filesystem::read(&get_netrc_path()?).ok()?;
+    find_in_netrc(&content, parsed.host_str()?)
+}
+
+/// Adds the credentials for `url` to `request`.
+pub fn authorize(request: RequestBuilder, credentials: Option<&Credentials>) -> RequestBuilder {
+    match credentials {
+        Some(credentials) => credentials.apply(request),
+        None => request,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn served(registry: &str, url: &str) -> bool {
+        is_served_by(&Registry::from_url(registry), &Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn serves_urls_on_the_registry_origin() {
+        assert!(served("https://reg.example.com", "https://reg.example.com/language/node"));
+        assert!(served("https://reg.example.com", "https://reg.example.com:443/files/node.tgz"));
+        assert!(served("https://reg.example.com/repo", "https://reg.example.com/repo"));
+        assert!(served("https://reg.example.com/repo", "https://reg.example.com/repo/node.tgz"));
+    }
+
+    #[test]
+    fn rejects_lookalike_hosts_and_paths() {
+        assert!(!served("https://reg.example.com", "https://reg.example.com.evil.net/x.tgz"));
+        assert!(!served("https://reg.example.com", "https://reg.example.comevil.net/x"));
+        assert!(!served("https://reg.example.com", "https://reg.example.com@evil.net/x.tgz"));
+        assert!(!served("https://reg.example.com", "http://reg.example.com/x.tgz"));
+        assert!(!served("https://reg.example.com", "https://reg.example.com:8443/x.tgz"));
+        assert!(!served("https://reg.example.com/repo", "https://reg.example.com/repository/x.tgz"));
+        assert!(!served("https://reg.example.com/repo", "https://reg.example.com/other/x.tgz"));
+    }
+}
@@ -0,0 +1,154 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use json;
+
+use crate::filesystem::{self, config::Config, get_pkit_cache_dir};
+use crate::formatter::{print_message, MessageType};
+
+use super::auth;
+use super::error::ApiError;
+use super::request;
+use super::signature::SIGNATURE_HEADER;
+
+pub const DEFAULT_CACHE_TTL: u64 = 3600;
+
+static REFRESH: AtomicBool = AtomicBool::new(false);
+
+/// Forces every lookup in this process to go to the registry (used by `--refresh`).
+pub fn set_refresh(refresh: bool) {
+    REFRESH.store(refresh, Ordering::Relaxed);
+}
+
+struct CacheEntry {
+    etag: Option<String>,
+    fetched_at: u64,
+    body: String,
+    signature: Option<String>,
+}
+
+/// A registry response body together with its detached signature, if any.
+pub struct Fetched {
+    pub body: String,
+    pub signature: Option<String>,
+}
+
+impl From<CacheEntry> for Fetched {
+    fn from(entry: CacheEntry) -> Self {
+        Fetched {
+            body: entry.body,
+            signature: entry.signature,
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn get_ttl() -> u64 {
+    Config::read().map(|config| config.cache_ttl).unwrap_or(DEFAULT_CACHE_TTL)
+}
+
+pub fn get_metadata_cache_dir() -> std::io::Result<PathBuf> {
+    Ok(get_pkit_cache_dir()?.join("metadata"))
+}
+
+/// Turns a URL into a file name that is safe on every platform.
+pub fn cache_key(url: &str) -> String {
+    let url = auth::redact_url(url);
+    url.split("://")
+        .last()
+        .unwrap_or(&url)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+        .collect()
+}
+
+fn entry_path(url: &str) -> Option<PathBuf> {
+    get_metadata_cache_dir().ok().map(|dir| dir.join(format!("{}.json", cache_key(url))))
+}
+
+fn read_entry(url: &str) -> Option<CacheEntry> {
+    let json_data = json::parse(&filesystem::read(&entry_path(url)?).ok()?).ok()?;
+    Some(CacheEntry {
+        etag: json_data["etag"].as_str().map(String::from),
+        fetched_at: json_data["fetched_at"].as_u64().unwrap_or(0),
+        body: json_data["body"].as_str()?.to_string(),
+        signature: json_data["signature"].as_str().map(String::from),
+    })
+}
+
+fn write_entry(url: &str, entry: &CacheEntry) {
+    let Some(path) = entry_path(url) else {
+        return;
+    };
+
+    let mut json_data = json::JsonValue::new_object();
+    json_data["url"] = auth::redact_url(url).into();
+    json_data["etag"] = entry.etag.clone().into();
+    json_data["fetched_at"] = entry.fetched_at.into();
+    json_data["body"] = entry.body.clone().into();
+    json_data["signature"] = entry.signature.clone().into();
+
+    // A cache that cannot be written is not worth failing the command over.
+    let _ = filesystem::write(&path, &json_data.dump());
+}
+
+/// Returns the body for `url`, serving it from the metadata cache while it is fresh.
+///
+/// Stale entries are revalidated with `If-None-Match`; if the registry cannot be reached
+/// the stale body is used instead of failing.
+pub async fn fetch(url: &str) -> Result<Fetched, ApiError> {
+    let cached = read_entry(url);
+
+    if let Some(entry) = &cached
+        && !REFRESH.load(Ordering::Relaxed)
+        && now().saturating_sub(entry.fetched_at) < get_ttl()
+    {
+        return Ok(Fetched {
+            body: entry.body.clone(),
+            signature: entry.signature.clone(),
+        });
+    }
+
+    let etag = cached.as_ref().and_then(|entry| entry.etag.as_deref());
+    let response = match request::get_conditional(url, etag).await {
+        Ok(response) => response,
+        Err(e) if !e.is_transient() => return Err(e),
+        Err(e) => {
+            if let Some(entry) = cached {
+                print_message(MessageType::Warning(&format!("{} - using cached data", e)));
+                return Ok(entry.into());
+            }
+            return Err(e);
+        }
+    };
+
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED
+        && let Some(mut entry) = cached
+    {
+        entry.fetched_at = now();
+        write_entry(url, &entry);
+        return Ok(entry.into());
+    }
+
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from)
+    };
+    let etag = header(reqwest::header::ETAG.as_str());
+    let signature = header(SIGNATURE_HEADER);
+    let body = response.text().await.map_err(|e| ApiError::decode(url, e.without_url()))?;
+
+    let entry = CacheEntry { etag, fetched_at: now(), body, signature };
+    write_entry(url, &entry);
+    Ok(entry.into())
+}
@@ -0,0 +1,143 @@
+use std::fmt;
+use std::io;
+
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::filesystem;
+
+use super::error::ApiError;
+use super::request;
+use super::static_index;
+
+pub const SHASUMS_FILE_NAME: &str = "SHASUMS256.txt";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChecksumAlgorithm {
+    Sha256,
+    Sha512,
+}
+
+impl ChecksumAlgorithm {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Sha256 => "sha256",
+            ChecksumAlgorithm::Sha512 => "sha512",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ChecksumAlgorithm> {
+        match name.to_lowercase().as_str() {
+            "sha256" => Some(ChecksumAlgorithm::Sha256),
+            "sha512" => Some(ChecksumAlgorithm::Sha512),
+            _ => None,
+        }
+    }
+}
+
+/// An expected or computed archive digest, stored as lowercase hex.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checksum {
+    pub algorithm: ChecksumAlgorithm,
+    pub value: String,
+}
+
+impl Checksum {
+    pub fn new(algorithm: ChecksumAlgorithm, value: &str) -> Checksum {
+        Checksum {
+            algorithm,
+            value: value.trim().to_lowercase(),
+        }
+    }
+
+    /// Parses the `<algorithm>:<hex>` form used in registry responses and pkit.json.
+    pub fn parse(text: &str) -> Option<Checksum> {
+        let (algorithm, value) = text.split_once(':')?;
+        Some(Checksum::new(ChecksumAlgorithm::from_name(algorithm)?, value))
+    }
+}
+
+impl fmt::Display for Checksum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.algorithm.name(), self.value)
+    }
+}
+
+/// Incrementally hashes a download as chunks arrive.
+pub enum Hasher {
+    Sha256(Sha256),
+    Sha512(Sha512),
+}
+
+impl Hasher {
+    pub fn new(algorithm: ChecksumAlgorithm) -> Hasher {
+        match algorithm {
+            ChecksumAlgorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+            ChecksumAlgorithm::Sha512 => Hasher::Sha512(Sha512::new()),
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Sha256(hasher) => hasher.update(data),
+            Hasher::Sha512(hasher) => hasher.update(data),
+        }
+    }
+
+    pub fn finalize(self) -> Checksum {
+        let (algorithm, bytes) = match self {
+            Hasher::Sha256(hasher) => (ChecksumAlgorithm::Sha256, hasher.finalize().to_vec()),
+            Hasher::Sha512(hasher) => (ChecksumAlgorithm::Sha512, hasher.finalize().to_vec()),
+        };
+        let value: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        Checksum { algorithm, value }
+    }
+}
+
+impl io::Write for Hasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Finds the entry for `file_name` in a `SHASUMS256.txt` style listing.
+fn find_in_shasums(listing: &str, file_name: &str) -> Option<Checksum> {
+    listing.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let hash = parts.next()?;
+        let name = parts.next()?.trim_start_matches('*');
+        (name == file_name).then(|| Checksum::new(ChecksumAlgorithm::Sha256, hash))
+    })
+}
+
+/// Looks for a `SHASUMS256.txt` next to `url` and returns the digest listed for it, if any.
+///
+/// The listing is optional, so it gets one attempt rather than the usual retries.
+pub async fn discover(url: &str) -> Option<Checksum> {
+    let (base, file_name) = url.rsplit_once('/')?;
+    let listing_url = format!("{}/{}", base, SHASUMS_FILE_NAME);
+
+    let listing = if static_index::is_local(&listing_url) {
+        filesystem::read(&static_index::to_path(&listing_url)).ok()?
+    } else {
+        request::get_once(&listing_url).await.ok()?.text().await.ok()?
+    };
+    find_in_shasums(&listing, file_name)
+}
+
+/// Compares a computed digest with the expected one.
+pub fn verify(url: &str, expected: &Checksum, actual: &Checksum) -> Result<(), ApiError> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(ApiError::ChecksumMismatch {
+            url: url.to_string(),
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+        })
+    }
+}
@@ -0,0 +1,128 @@
+use std::fmt;
+use std::io;
+
+/// Everything that can go wrong while talking to a registry.
+#[derive(Debug)]
+pub enum ApiError {
+    /// The request never got a response (DNS, connection refused, TLS, ...).
+    Network { url: String, message: String },
+    /// The registry answered with a non-success status code.
+    Http { url: String, status: u16 },
+    /// The response body could not be read or parsed.
+    Decode { url: String, message: String },
+    /// The requested language or version does not exist.
+    NotFound(String),
+    /// The downloaded archive does not match the published digest.
+    ChecksumMismatch { url: String, expected: String, actual: String },
+    /// Registry metadata is unsigned or signed by an untrusted key.
+    Signature { url: String, message: String },
+    /// A build exists but cannot run on this system (wrong libc, glibc too old, ...).
+    Incompatible(String),
+    /// The registry uses a newer response schema than this pkit understands.
+    UnsupportedSchema { url: String, version: u64 },
+    /// The network settings in pkit.json are unusable (bad proxy URL, unreadable certificate, ...).
+    Config(String),
+    /// A local file could not be written while downloading.
+    Io(io::Error),
+}
+
+impl ApiError {
+    pub fn network(url: &str, error: impl fmt::Display) -> ApiError {
+        ApiError::Network { url: url.to_string(), message: error.to_string() }
+    }
+
+    pub fn decode(url: &str, error: impl fmt::Display) -> ApiError {
+        ApiError::Decode { url: url.to_string(), message: error.to_string() }
+    }
+
+    pub fn signature(url: &str, message: &str) -> ApiError {
+        ApiError::Signature { url: url.to_string(), message: message.to_string() }
+    }
+
+    /// Short heading used for the error box in command output.
+    pub fn title(&self) -> &'static str {
+        match self {
+            ApiError::Network { .. } => "Network Error",
+            ApiError::Http { .. } => "Registry Error",
+            ApiError::Decode { .. } => "Invalid Registry Response",
+            ApiError::NotFound(_) => "Not Found",
+            ApiError::ChecksumMismatch { .. } => "Checksum Mismatch",
+            ApiError::Signature { .. } => "Signature Verification Failed",
+            ApiError::Incompatible(_) => "Incompatible Build",
+            ApiError::UnsupportedSchema { .. } => "Unsupported Registry Schema",
+            ApiError::Config(_) => "Configuration Error",
+            ApiError::Io(_) => "File Error",
+        }
+    }
+
+    /// Process exit code for this kind of failure.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ApiError::NotFound(_) => 2,
+            ApiError::Network { .. } => 3,
+            ApiError::Http { .. } => 4,
+            ApiError::Decode { .. } => 5,
+            ApiError::Io(_) => 6,
+            ApiError::ChecksumMismatch { .. } => 7,
+            ApiError::Signature { .. } => 8,
+            ApiError::Config(_) => 9,
+            ApiError::UnsupportedSchema { .. } => 10,
+            ApiError::Incompatible(_) => 11,
+        }
+    }
+
+    /// Whether retrying the same request might succeed.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ApiError::Network { .. } => true,
+            ApiError::Http { status, .. } => *status >= 500 || *status == 408 || *status == 429,
+            _ => false,
+        }
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, ApiError::NotFound(_))
+    }
+}
+
+/// Drops the query string so filters don't clutter (or colorize) error output,
+/// and any credentials embedded in the URL.
+fn display_url(url: &str) -> String {
+    super::auth::redact_url(url.split('?').next().unwrap_or(url))
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Network { url, message } => write!(f, "Could not reach {}: {}", display_url(url), message),
+            ApiError::Http { url, status: status @ (401 | 403) } => {
+                write!(f, "{} responded with HTTP {} - check the credentials for this registry", display_url(url), status)
+            }
+            ApiError::Http { url, status } => write!(f, "{} responded with HTTP {}", display_url(url), status),
+            ApiError::Decode { url, message } => write!(f, "Could not read the response from {}: {}", display_url(url), message),
+            ApiError::NotFound(what) => write!(f, "{}", what),
+            ApiError::Incompatible(reason) => write!(f, "{}", reason),
+            ApiError::ChecksumMismatch { url, expected, actual } => {
+                write!(f, "{} failed verification (expected {}, got {})", display_url(url), expected, actual)
+            }
+            ApiError::Signature { url, message } => write!(f, "Could not verify {}: {}", display_url(url), message),
+            ApiError::UnsupportedSchema { url, version } => write!(
+                f,
+                "{} uses registry schema {}, but this pkit only understands up to {}; please upgrade pkit",
+                display_url(url),
+                version,
+                super::models::SCHEMA_VERSION
+            ),
+            ApiError::Config(message) => write!(f, "{}", message),
+            ApiError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<io::Error> for ApiError {
+    fn from(error: io::Error) -> Self {
+        ApiError::Io(error)
+    }
+}
@@ -1,88 +1,418 @@
+use crate::filesystem::config::{Config, Registry};
+use crate::suggest;
 
-use std::env::consts::{OS, ARCH};
-
-use json;
+use models::{Flag, VersionEntry};
+use target::Libc;
 
 pub mod request;
+pub mod registry;
+pub mod error;
+pub mod cache;
+pub mod checksum;
+pub mod signature;
+pub mod archive;
+pub mod static_index;
+pub mod models;
+pub mod auth;
+pub mod target;
 
-const API_URL: &str = "https://pkit.sirblob.co/api";
+pub use checksum::{Checksum, ChecksumAlgorithm};
+pub use error::ApiError;
+pub use models::SCHEMA_VERSION;
+pub use registry::{get_registries, set_registry_override, DEFAULT_REGISTRY_URL};
+pub use target::{get_arch, get_platform, set_target_override};
 
+#[derive(Clone)]
 pub struct Version {
     pub language: String,
     pub version: String,
     pub platform: String,
     pub arch: String,
-    pub url: String
+    pub url: String,
+    pub registry: String,
+    pub checksum: Option<Checksum>,
+    pub mirrors: Vec<String>,
+    pub release_date: Option<String>,
+    pub channel: Option<String>,
+    pub lts: bool,
+    /// LTS codename, e.g. `Iron` for node 20.
+    pub codename: Option<String>,
+    pub deprecated: bool,
+    /// End-of-life date; empty when the registry only says support has ended.
+    pub eol: Option<String>,
+    pub libc: Option<String>,
+    pub min_glibc: Option<String>
+}
+
+impl Version {
+    /// Every URL the archive can be fetched from, in the order they should be tried:
+    /// the primary URL, the registry's mirrors, then local mirrors from pkit.json.
+    ///
+    /// Only a registry on the local filesystem may point at local files; a remote one that
+    /// does is refused rather than letting it pick files off this machine.
+    pub fn download_urls(&self) -> Result<Vec<String>, ApiError> {
+        if !static_index::is_local(&self.registry)
+            && let Some(url) = std::iter::once(&self.url).chain(&self.mirrors).find(|url| static_index::is_local(url))
+        {
+            return Err(ApiError::decode(
+                &self.registry,
+                format!("{} {} points to the local file {}, which only a local registry may do", self.language, self.version, url),
+            ));
+        }
+
+        let mut urls: Vec<String> = Vec::new();
+        let mut push = |url: String| {
+            if !urls.contains(&url) {
+                urls.push(url);
+            }
+        };
+
+        push(self.url.clone());
+        for mirror in &self.mirrors {
+            push(mirror.clone());
+        }
+
+        let local_mirrors = Config::read().map(|config| config.mirrors).unwrap_or_default();
+        for mirror in &local_mirrors {
+            if let Some(rest) = self.url.strip_prefix(&mirror.prefix) {
+                for base in &mirror.urls {
+                    push(format!("{}{}", base, rest));
+                }
+            }
+        }
+
+        Ok(urls)
+    }
+
+    /// Why this build can't run on the system it is installed for, if it can't.
+    ///
+    /// Builds that don't declare a libc are assumed to work, as are cross-installs to
+    /// another platform since their libc is unknown.
+    pub fn libc_issue(&self) -> Option<String> {
+        let host = target::get_libc()?;
+        let name = format!("{} {} ({}-{})", self.language, self.version, self.platform, self.arch);
+
+        match self.libc.as_deref() {
+            Some(libc) if !libc.eq_ignore_ascii_case(host.name()) => {
+                return Some(format!("{} is built for {}, but this system uses {}", name, libc, host));
+            }
+            _ => {}
+        }
+
+        let required = self.min_glibc.as_deref().and_then(target::parse_glibc_version)?;
+        match host {
+            Libc::Musl => Some(format!("{} needs glibc {}.{}, but this system uses musl", name, required.0, required.1)),
+            Libc::Glibc(Some(found)) if found < required => Some(format!(
+                "{} needs glibc {}.{} or newer, but this system has {}",
+                name, required.0, required.1, host
+            )),
+            Libc::Glibc(_) => None,
+        }
+    }
+
+    /// Whether support for this version has ended as of today.
+    pub fn is_eol(&self) -> bool {
+        match &self.eol {
+            Some(date) => date.is_empty() || date.as_str() <= today().as_str(),
+            None => false,
+        }
+    }
+}
+
+/// Today's date (UTC) as `YYYY-MM-DD`, for comparing with registry dates.
+pub fn today() -> String {
+    let days = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86_400)
+        .unwrap_or(0) as i64;
+
+    // Days since 1970-01-01 to a civil date (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
 }
 
 pub struct Language {
     pub name: String,
-    pub versions: Vec<Version>
+    pub versions: Vec<Version>,
+    /// Builds that were left out because they can't run here (see [`Version::libc_issue`]).
+    pub incompatible: Vec<Version>
 }
 
-fn get_filters() -> String {
-    let mut filters:String = String::new();
+fn get_filters(arch: &str) -> String {
+    let mut filters = format!("?platform={}&arch={}", get_platform(), arch);
+    match target::get_libc() {
+        Some(Libc::Glibc(Some((major, minor)))) => filters.push_str(&format!("&libc=glibc&glibc={}.{}", major, minor)),
+        Some(libc) => filters.push_str(&format!("&libc={}", libc.name())),
+        None => {}
+    }
+    filters
+}
 
-    filters.push_str("?platform=");
-    if OS == "windows" { filters.push_str("win"); }
-    if OS == "macos" { filters.push_str("darwin"); }
-    if OS == "linux" { filters.push_str("linux"); }
+/// Whether `version` was built for the target. Dynamic registries filter on the server;
+/// static indexes hold every target, so their results are filtered here instead.
+fn matches_filters(version: &Version, arch: &str) -> bool {
+    version.platform == get_platform() && version.arch == arch
+}
 
-    filters.push_str("&arch=");
-    if ARCH == "x86_64" { filters.push_str("x64"); }
-    else if (ARCH == "arm" && cfg!(target_pointer_width = "64")) || ARCH == "aarch64" { filters.push_str("arm64"); }
-    else { filters.push_str("none"); }
+/// Fetches a registry document, verifying its signature before it is used.
+///
+/// `path` is the API path without the host, e.g. `language/node`; platform filters are added
+/// for version lookups. Returns the URL that was read along with the body.
+async fn fetch_document(registry: &Registry, path: &str, arch: &str) -> Result<(String, String), ApiError> {
+    let (url, fetched) = if registry.is_static() {
+        static_index::fetch(registry, path).await?
+    } else {
+        let filters = if path == "language" { String::new() } else { get_filters(arch) };
+        let url = format!("{}/{}{}", registry.url, path, filters);
+        let fetched = cache::fetch(&url).await?;
+        (url, fetched)
+    };
 
-    filters
+    signature::verify(&url, path, &fetched.body, fetched.signature.as_deref(), &registry.public_keys)?;
+    Ok((url, fetched.body))
 }
 
-pub async fn get_languages() -> Vec<String> {
-    let res = request::get(&format!("{}/language", API_URL)).await;
-    let json_data: json::JsonValue = json::parse(&res.text().await.unwrap()).unwrap();
+/// Keeps the most useful error seen so far: a real failure beats a plain "not found".
+fn keep_error(current: Option<ApiError>, next: ApiError) -> Option<ApiError> {
+    match current {
+        Some(current) if next.is_not_found() => Some(current),
+        _ => Some(next),
+    }
+}
 
-    let mut languages: Vec<String> = Vec::new();
-    for lang in json_data.members() {
-        languages.push(lang.to_string());
+/// Reads either `"checksum": "<algorithm>:<hex>"` or a bare `sha512`/`sha256` field.
+fn parse_checksum(entry: &VersionEntry) -> Option<Checksum> {
+    if let Some(checksum) = entry.checksum.as_deref().and_then(Checksum::parse) {
+        return Some(checksum);
     }
+    if let Some(value) = &entry.sha512 {
+        return Some(Checksum::new(ChecksumAlgorithm::Sha512, value));
+    }
+    entry.sha256.as_ref().map(|value| Checksum::new(ChecksumAlgorithm::Sha256, value))
+}
 
+fn to_version(entry: VersionEntry, language: &str, registry: &str) -> Version {
+    Version {
+        checksum: parse_checksum(&entry),
+        language: entry.name.unwrap_or_else(|| language.to_string()),
+        version: entry.version,
+        platform: entry.platform,
+        arch: entry.arch,
+        url: entry.url,
+        registry: registry.to_string(),
+        mirrors: entry.mirrors,
+        release_date: entry.release_date,
+        channel: entry.channel,
+        lts: entry.lts.as_ref().is_some_and(|lts| lts.is_set()),
+        codename: entry.lts.as_ref().and_then(|lts| lts.name()).map(String::from),
+        deprecated: entry.deprecated.as_ref().is_some_and(|deprecated| deprecated.is_set()),
+        eol: match entry.eol {
+            Some(Flag::Named(date)) if !date.is_empty() => Some(date),
+            Some(Flag::Enabled(true)) => Some(String::new()),
+            _ => None,
+        },
+        libc: entry.libc,
+        min_glibc: entry.min_glibc
+    }
+}
+
+/// A language offered by a registry, with the other names it can be asked for by.
+pub struct LanguageName {
+    pub name: String,
+    /// Alternative names such as `nodejs` for `node`, as published by the registry.
+    pub aliases: Vec<String>,
+}
+
+/// Lists the languages offered by every configured registry, without duplicates.
+///
+/// Registries that fail are skipped; an error is only returned when none of them answered.
+pub async fn get_language_names() -> Result<Vec<LanguageName>, ApiError> {
+    let mut languages: Vec<LanguageName> = Vec::new();
+    let mut answered = false;
+    let mut last_error: Option<ApiError> = None;
+
+    for registry in get_registries() {
+        let entries = match fetch_document(&registry, "language", get_arch()).await
+            .and_then(|(url, body)| models::parse_languages(&url, &body))
+        {
+            Ok(entries) => entries,
+            Err(e) => {
+                last_error = keep_error(last_error, e);
+                continue;
+            }
+        };
+
+        answered = true;
+        for entry in entries {
+            let aliases = entry.aliases().iter().cloned();
+            match languages.iter_mut().find(|l| l.name.eq_ignore_ascii_case(entry.name())) {
+                Some(language) => language.aliases.extend(aliases),
+                None => languages.push(LanguageName { name: entry.name().to_string(), aliases: aliases.collect() }),
+            }
+        }
+    }
+
+    match last_error {
+        Some(e) if !answered => Err(e),
+        _ => Ok(languages),
+    }
+}
+
+/// Lists the names of the languages offered by every configured registry.
+pub async fn get_languages() -> Result<Vec<String>, ApiError> {
+    Ok(get_language_names().await?.into_iter().map(|language| language.name).collect())
+}
+
+/// Maps `language` to the name the registries use for it, matching names and aliases
+/// without regard to case, so `nodejs` or `Golang` find `node` and `go`.
+pub async fn resolve_language(language: &str) -> Result<String, ApiError> {
+    let languages = get_language_names().await?;
     languages
+        .iter()
+        .find(|l| l.name.eq_ignore_ascii_case(language))
+        .or_else(|| languages.iter().find(|l| l.aliases.iter().any(|alias| alias.eq_ignore_ascii_case(language))))
+        .map(|l| l.name.clone())
+        .ok_or_else(|| ApiError::NotFound(format!("Language '{}' not found", language)))
 }
 
-pub async fn get_language(language: &str) -> Language {
-    let res = request::get(&format!("{}/language/{}{}", API_URL, language.to_lowercase(), get_filters())).await;
-    let json_data: json::JsonValue = json::parse(&res.text().await.unwrap()).unwrap();
+/// Language names and aliases close to `language`, for "did you mean" hints.
+pub async fn suggest_languages(language: &str) -> Vec<String> {
+    let Ok(languages) = get_language_names().await else {
+        return Vec::new();
+    };
+    let names = languages.iter().flat_map(|l| std::iter::once(&l.name).chain(&l.aliases));
+    suggest::closest(language, names.map(String::as_str))
+}
 
+/// Versions of `language` close to `version`, for "did you mean" hints.
+pub async fn suggest_versions(language: &str, version: &str) -> Vec<String> {
+    let Ok(language_data) = get_language(language).await else {
+        return Vec::new();
+    };
+    suggest::closest(version, language_data.versions.iter().map(|v| v.version.as_str()))
+}
+
+/// Merges the versions of a language across registries.
+///
+/// When two registries publish the same version for the same target, the one listed first wins.
+pub async fn get_language(language: &str) -> Result<Language, ApiError> {
+    get_language_for_arch(language, get_arch()).await
+}
+
+/// Like [`get_language`], but for builds of another architecture than the target one.
+pub async fn get_language_for_arch(language: &str, arch: &str) -> Result<Language, ApiError> {
     let mut versions: Vec<Version> = Vec::new();
-    for data in json_data.members() {
-        versions.push(Version {
-            language: data["name"].to_string(),
-            version: data["version"].to_string(),
-            platform: data["platform"].to_string(),
-            arch: data["arch"].to_string(),
-            url: data["url"].to_string()
+    let mut incompatible: Vec<Version> = Vec::new();
+    let mut last_error: Option<ApiError> = None;
+
+    for registry in get_registries() {
+        let path = format!("language/{}", language.to_lowercase());
+        let entries = match fetch_document(&registry, &path, arch).await
+            .and_then(|(url, body)| models::parse_versions(&url, &body))
+        {
+            Ok(entries) => entries,
+            Err(e) => {
+                last_error = keep_error(last_error, e);
+                continue;
+            }
+        };
+
+        for entry in entries {
+            let version = to_version(entry, language, &registry.url);
+            if registry.is_static() && !matches_filters(&version, arch) {
+                continue;
+            }
+            if version.libc_issue().is_some() {
+                incompatible.push(version);
+                continue;
+            }
+            let duplicate = versions.iter().any(|v| {
+                v.version == version.version && v.platform == version.platform && v.arch == version.arch
+            });
+            if !duplicate {
+                versions.push(version);
+            }
+        }
+    }
+
+    if versions.is_empty()
+        && incompatible.is_empty()
+        && let Some(e) = last_error
+    {
+        return Err(match e {
+            ApiError::NotFound(_) => ApiError::NotFound(format!("Language '{}' not found", language)),
+            e => e,
         });
     }
 
-    Language {
+    Ok(Language {
         name: language.to_string(),
-        versions
-    }
+        versions,
+        incompatible
+    })
+}
+
+/// Fetches a single version from the first registry that has it.
+pub async fn get_language_version(language: &str, version: &str) -> Result<Version, ApiError> {
+    get_language_version_for_arch(language, version, get_arch()).await
 }
 
-pub async fn get_language_version(language: &str, version: &str) -> Version {
-    let res = request::get(&format!("{}/language/{}/{}{}", API_URL, language.to_lowercase(), version, get_filters())).await;
-    let json_data: json::JsonValue = json::parse(&res.text().await.unwrap()).unwrap();
+/// Like [`get_language_version`], but for a build of another architecture than the target one.
+pub async fn get_language_version_for_arch(language: &str, version: &str, arch: &str) -> Result<Version, ApiError> {
+    let mut last_error: Option<ApiError> = None;
 
-    Version {
-        language: json_data["name"].to_string(),
-        version: json_data["version"].to_string(),
-        platform: json_data["platform"].to_string(),
-        arch: json_data["arch"].to_string(),
-        url: json_data["url"].to_string()
+    for registry in get_registries() {
+        // Static indexes have no per-version documents; pick the version out of the list.
+        if registry.is_static() {
+            let path = format!("language/{}", language.to_lowercase());
+            match fetch_document(&registry, &path, arch).await.and_then(|(url, body)| models::parse_versions(&url, &body)) {
+                Ok(entries) => {
+                    let candidates = entries
+                        .into_iter()
+                        .map(|entry| to_version(entry, language, &registry.url))
+                        .filter(|v| v.version == version && matches_filters(v, arch));
+                    for candidate in candidates {
+                        match candidate.libc_issue() {
+                            None => return Ok(candidate),
+                            Some(issue) => last_error = keep_error(last_error, ApiError::Incompatible(issue)),
+                        }
+                    }
+                }
+                Err(e) => last_error = keep_error(last_error, e),
+            }
+            continue;
+        }
+
+        let path = format!("language/{}/{}", language.to_lowercase(), version);
+        match fetch_document(&registry, &path, arch).await.and_then(|(url, body)| models::parse_version(&url, &body)) {
+            Ok(entry) => {
+                let candidate = to_version(entry, language, &registry.url);
+                match candidate.libc_issue() {
+                    None => return Ok(candidate),
+                    Some(issue) => last_error = keep_error(last_error, ApiError::Incompatible(issue)),
+                }
+            }
+            Err(e) => last_error = keep_error(last_error, e),
+        }
     }
-}
 
+    Err(match last_error {
+        Some(ApiError::NotFound(_)) | None => {
+            ApiError::NotFound(format!("Version '{}' not found for language '{}'", version, language))
+        }
+        Some(e) => e,
+    })
+}
 
 // pub async fn download_language_version(language: &str, version: &str) {
 
-// }
\ No newline at end of file
+// }
@@ -0,0 +1,141 @@
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::error::ApiError;
+
+/// Newest registry response schema this pkit understands.
+///
+/// Responses without a `schema_version` are treated as schema 1. Fields added within a schema
+/// are ignored by older clients, so only breaking changes need a new number.
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// A field that is either a flag or carries a name, such as `"lts": true` or `"lts": "Iron"`.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Flag {
+    Enabled(bool),
+    Named(String),
+}
+
+impl Flag {
+    pub fn is_set(&self) -> bool {
+        match self {
+            Flag::Enabled(enabled) => *enabled,
+            Flag::Named(name) => !name.is_empty(),
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Flag::Named(name) if !name.is_empty() => Some(name),
+            _ => None,
+        }
+    }
+}
+
+/// An entry of the `/language` list: a bare name, or a name with the aliases it answers to.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum LanguageEntry {
+    Name(String),
+    Detailed {
+        name: String,
+        #[serde(default)]
+        aliases: Vec<String>,
+    },
+}
+
+impl LanguageEntry {
+    pub fn name(&self) -> &str {
+        match self {
+            LanguageEntry::Name(name) | LanguageEntry::Detailed { name, .. } => name,
+        }
+    }
+
+    pub fn aliases(&self) -> &[String] {
+        match self {
+            LanguageEntry::Name(_) => &[],
+            LanguageEntry::Detailed { aliases, .. } => aliases,
+        }
+    }
+}
+
+/// One downloadable build as published by a registry.
+///
+/// Unknown fields are ignored so registries can add metadata without breaking older clients.
+#[derive(Debug, Deserialize)]
+pub struct VersionEntry {
+    pub name: Option<String>,
+    pub version: String,
+    pub platform: String,
+    pub arch: String,
+    pub url: String,
+    pub checksum: Option<String>,
+    pub sha256: Option<String>,
+    pub sha512: Option<String>,
+    #[serde(default)]
+    pub mirrors: Vec<String>,
+    /// Release date as `YYYY-MM-DD`.
+    #[serde(alias = "date")]
+    pub release_date: Option<String>,
+    /// Release channel such as `stable`, `beta` or `rc`.
+    pub channel: Option<String>,
+    /// `true` or the LTS codename.
+    pub lts: Option<Flag>,
+    pub deprecated: Option<Flag>,
+    /// End-of-life date as `YYYY-MM-DD`, or `true` when support already ended.
+    #[serde(alias = "end_of_life")]
+    pub eol: Option<Flag>,
+    /// `glibc` or `musl` for Linux builds that depend on one of them.
+    pub libc: Option<String>,
+    /// Oldest glibc the build runs on, e.g. `2.28`.
+    pub min_glibc: Option<String>,
+}
+
+/// Rejects documents that declare a schema newer than [`SCHEMA_VERSION`].
+fn check_schema(url: &str, document: &Value) -> Result<(), ApiError> {
+    let version = match document.get("schema_version") {
+        None => return Ok(()),
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| ApiError::decode(url, "schema_version must be a positive integer"))?,
+    };
+
+    if version > SCHEMA_VERSION {
+        return Err(ApiError::UnsupportedSchema { url: url.to_string(), version });
+    }
+    Ok(())
+}
+
+fn parse_document(url: &str, body: &str) -> Result<Value, ApiError> {
+    let document: Value = serde_json::from_str(body).map_err(|e| ApiError::decode(url, e))?;
+    check_schema(url, &document)?;
+    Ok(document)
+}
+
+/// Lists may be a bare array or wrapped as `{ "schema_version": 1, "<field>": [...] }`.
+fn parse_list<T: DeserializeOwned>(url: &str, body: &str, field: &str) -> Result<Vec<T>, ApiError> {
+    let items = match parse_document(url, body)? {
+        Value::Object(mut object) => object
+            .remove(field)
+            .ok_or_else(|| ApiError::decode(url, format!("missing field `{}`", field)))?,
+        document => document,
+    };
+    serde_json::from_value(items).map_err(|e| ApiError::decode(url, e))
+}
+
+/// Parses a `/language` response.
+pub fn parse_languages(url: &str, body: &str) -> Result<Vec<LanguageEntry>, ApiError> {
+    parse_list(url, body, "languages")
+}
+
+/// Parses a `/language/<name>` response.
+pub fn parse_versions(url: &str, body: &str) -> Result<Vec<VersionEntry>, ApiError> {
+    parse_list(url, body, "versions")
+}
+
+/// Parses a `/language/<name>/<version>` response.
+pub fn parse_version(url: &str, body: &str) -> Result<VersionEntry, ApiError> {
+    serde_json::from_value(parse_document(url, body)?).map_err(|e| ApiError::decode(url, e))
+}
@@ -0,0 +1,58 @@
+use std::env;
+use std::sync::OnceLock;
+
+use crate::filesystem::config::{Config, Registry};
+
+pub const DEFAULT_REGISTRY_URL: &str = "https://pkit.sirblob.co/api";
+pub const REGISTRY_ENV_VAR: &str = "PKIT_REGISTRY";
+
+static REGISTRY_OVERRIDE: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Overrides the configured registries for the rest of the process (used by `--registry`).
+pub fn set_registry_override(urls: &[String]) {
+    let _ = REGISTRY_OVERRIDE.set(urls.to_vec());
+}
+
+/// Builds registry entries for ad-hoc URLs, keeping the settings (such as trusted keys)
+/// of any configured registry with the same URL.
+fn registries_from_urls<'a>(urls: impl Iterator<Item = &'a str>, configured: &[Registry]) -> Vec<Registry> {
+    urls.map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(|url| {
+            let candidate = Registry::from_url(url);
+            configured
+                .iter()
+                .find(|registry| registry.url == candidate.url)
+                .cloned()
+                .unwrap_or(candidate)
+        })
+        .collect()
+}
+
+/// Returns the registries to query, in priority order.
+///
+/// `--registry` wins over `PKIT_REGISTRY`, which wins over `registries` in pkit.json.
+/// When nothing is configured the public pkit registry is used.
+pub fn get_registries() -> Vec<Registry> {
+    let configured = Config::read().map(|config| config.registries).unwrap_or_default();
+
+    if let Some(urls) = REGISTRY_OVERRIDE.get() {
+        let registries = registries_from_urls(urls.iter().map(String::as_str), &configured);
+        if !registries.is_empty() {
+            return registries;
+        }
+    }
+
+    if let Ok(value) = env::var(REGISTRY_ENV_VAR) {
+        let registries = registries_from_urls(value.split(','), &configured);
+        if !registries.is_empty() {
+            return registries;
+        }
+    }
+
+    if !configured.is_empty() {
+        return configured;
+    }
+
+    vec![Registry::from_url(DEFAULT_REGISTRY_URL)]
+}
@@ -1,72 +1,408 @@
-
 use std::fs;
-use std::fs::File;
-use std::io::Write;
-use std::path::Path;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use indicatif::{ProgressBar, ProgressStyle};
 use futures_util::stream::StreamExt;
 
 use reqwest;
 
-pub async fn get(url: &str) -> reqwest::Response {
-    let client: reqwest::Client = reqwest::Client::new();
+use crate::filesystem::{self, get_pkit_cache_dir};
+use crate::filesystem::config::{Config, NetworkSettings};
+use crate::formatter::{print_message, MessageType};
+
+use super::auth;
+use super::cache;
+use super::checksum::{self, Checksum, ChecksumAlgorithm, Hasher};
+use super::error::ApiError;
+use super::static_index;
+
+const RETRY_BASE_DELAY_MS: u64 = 500;
+const RETRY_MAX_DELAY_MS: u64 = 8000;
+
+fn check_status(url: &str, resp: reqwest::Response) -> Result<reqwest::Response, ApiError> {
+    let status = resp.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Err(ApiError::NotFound(format!("{} does not exist", url)));
+    }
+    if !status.is_success() {
+        return Err(ApiError::Http { url: url.to_string(), status: status.as_u16() });
+    }
+    Ok(resp)
+}
+
+/// Builds a client from the `network` section of pkit.json: timeouts, proxy and extra root
+/// certificates. Every request pkit makes goes through a client built here.
+fn build_client() -> Result<reqwest::Client, ApiError> {
+    let settings = get_network_settings();
+
+    let mut builder = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(settings.connect_timeout))
+        .read_timeout(Duration::from_secs(settings.read_timeout));
+
+    if let Some(proxy_url) = &settings.proxy {
+        let no_proxy = match &settings.no_proxy {
+            Some(list) => reqwest::NoProxy::from_string(list),
+            None => reqwest::NoProxy::from_env(),
+        };
+        let proxy = reqwest::Proxy::all(proxy_url)
+            .map_err(|e| ApiError::Config(format!("Invalid proxy '{}': {}", proxy_url, e)))?
+            .no_proxy(no_proxy);
+        builder = builder.proxy(proxy);
+    }
+
+    for path in &settings.ca_certificates {
+        let pem = fs::read(path)
+            .map_err(|e| ApiError::Config(format!("Could not read CA certificate '{}': {}", path, e)))?;
+        let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+            .map_err(|e| ApiError::Config(format!("Invalid CA certificate '{}': {}", path, e)))?;
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    builder
+        .build()
+        .map_err(|e| ApiError::Config(format!("Could not create HTTP client: {}", e)))
+}
+
+fn get_network_settings() -> NetworkSettings {
+    Config::read().map(|config| config.network).unwrap_or_default()
+}
 
-    let resp: reqwest::Response = client.get(url)
+fn backoff_delay(attempt: u32) -> Duration {
+    let delay = RETRY_BASE_DELAY_MS.saturating_mul(1 << attempt.saturating_sub(1).min(16));
+    Duration::from_millis(delay.min(RETRY_MAX_DELAY_MS))
+}
+
+/// Runs `attempt` until it succeeds, fails permanently, or the configured retries run out.
+///
+/// Transient failures (connection problems, timeouts, 5xx/429 responses) are retried with
+/// exponential backoff and each retry is reported to the user. Downloads pass their progress
+/// bar so the notice is printed above it instead of through it.
+async fn with_retries<T, F, Fut>(pb: Option<&ProgressBar>, mut attempt: F) -> Result<T, ApiError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, ApiError>>,
+{
+    let retries = get_network_settings().retries;
+    let mut tries = 0;
+
+    loop {
+        match attempt().await {
+            Err(e) if e.is_transient() && tries < retries => {
+                tries += 1;
+                let delay = backoff_delay(tries);
+                let notice = format!(
+                    "{} - retrying in {:.1}s (attempt {}/{})",
+                    e,
+                    delay.as_secs_f32(),
+                    tries + 1,
+                    retries + 1
+                );
+                match pb {
+                    Some(pb) => pb.suspend(|| print_message(MessageType::Warning(&notice))),
+                    None => print_message(MessageType::Warning(&notice)),
+                }
+                tokio::time::sleep(delay).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+pub async fn get(url: &str) -> Result<reqwest::Response, ApiError> {
+    get_conditional(url, None).await
+}
+
+/// Like [`get`], but makes a single attempt. For optional lookups that shouldn't hold up
+/// the command with retries when the host is unreachable.
+pub async fn get_once(url: &str) -> Result<reqwest::Response, ApiError> {
+    let client: reqwest::Client = build_client()?;
+    let credentials = auth::get_credentials(url);
+    send_get(&client, url, credentials.as_ref(), None).await
+}
+
+/// Like [`get`], but sends `If-None-Match` and lets a `304 Not Modified` through.
+pub async fn get_conditional(url: &str, etag: Option<&str>) -> Result<reqwest::Response, ApiError> {
+    let client: reqwest::Client = build_client()?;
+    let credentials = auth::get_credentials(url);
+
+    with_retries(None, || send_get(&client, url, credentials.as_ref(), etag)).await
+}
+
+async fn send_get(
+    client: &reqwest::Client,
+    url: &str,
+    credentials: Option<&auth::Credentials>,
+    etag: Option<&str>,
+) -> Result<reqwest::Response, ApiError> {
+    let mut request = auth::authorize(client.get(url), credentials);
+    if let Some(etag) = etag {
+        request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+    }
+
+    let resp: reqwest::Response = request
         .send()
         .await
-        .expect("Failed to send request");
-    resp
+        .map_err(|e| ApiError::network(url, e.without_url()))?;
+    if resp.status() == reqwest::StatusCode::NOT_MODIFIED {
+        return Ok(resp);
+    }
+    check_status(url, resp)
 }
 
-pub async fn post(url: &str) {  
-    let client: reqwest::Client = reqwest::Client::new();
+pub async fn post(url: &str) -> Result<reqwest::StatusCode, ApiError> {
+    let client: reqwest::Client = build_client()?;
 
     let resp: reqwest::Response = client.post(url)
         .send()
         .await
-        .expect("Failed to send request");
+        .map_err(|e| ApiError::network(url, e.without_url()))?;
 
-    println!("Status: {:?}", resp.status());
+    Ok(resp.status())
 }
 
 
-pub async fn download(url: &str, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+/// Directory holding interrupted downloads waiting to be resumed.
+pub fn get_partial_download_dir() -> io::Result<PathBuf> {
+    Ok(get_pkit_cache_dir()?.join("downloads"))
+}
+
+/// Location of the partial download for `url` and of the file recording its validator.
+fn partial_paths(url: &str) -> io::Result<(PathBuf, PathBuf)> {
+    let dir = get_partial_download_dir()?;
+    fs::create_dir_all(&dir)?;
+    let key = cache::cache_key(url);
+    Ok((dir.join(format!("{}.part", key)), dir.join(format!("{}.part.json", key))))
+}
+
+fn read_validator(meta_path: &Path) -> Option<String> {
+    let json_data = json::parse(&filesystem::read(meta_path).ok()?).ok()?;
+    json_data["validator"].as_str().map(String::from)
+}
+
+fn write_validator(meta_path: &Path, url: &str, validator: &str) {
+    let mut json_data = json::JsonValue::new_object();
+    json_data["url"] = url.into();
+    json_data["validator"] = validator.into();
+    let _ = filesystem::write(meta_path, &json_data.dump());
+}
+
+/// Picks the value to send back in `If-Range`: a strong ETag, otherwise `Last-Modified`.
+fn get_validator(response: &reqwest::Response) -> Option<String> {
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from)
+    };
+    header(reqwest::header::ETAG)
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| header(reqwest::header::LAST_MODIFIED))
+}
+
+fn remove_partial(part_path: &Path, meta_path: &Path) {
+    let _ = fs::remove_file(part_path);
+    let _ = fs::remove_file(meta_path);
+}
+
+async fn send_download_request(url: &str, resume: Option<(u64, &str)>) -> Result<reqwest::Response, ApiError> {
+    let client = build_client()?;
 
-    if let Some(parent) = Path::new(path).parent() {
-        if !parent.exists() {
-            fs::create_dir_all(parent)?;
+    let mut request = auth::authorize(client.get(url), auth::get_credentials(url).as_ref());
+    if let Some((offset, validator)) = resume {
+        request = request
+            .header(reqwest::header::RANGE, format!("bytes={}-", offset))
+            .header(reqwest::header::IF_RANGE, validator);
+    }
+
+    request.send().await.map_err(|e| ApiError::network(url, e.without_url()))
+}
+
+/// Downloads `url` to `path`, hashing it on the way.
+///
+/// Data is written to a `.part` file in the cache directory first. If an earlier attempt left
+/// one behind, the download resumes with a `Range` request guarded by `If-Range`; servers that
+/// ignore it simply send the whole file again.
+///
+/// Transient failures are retried, resuming from whatever already reached the `.part` file.
+/// Progress is drawn on `pb`, which may belong to a `MultiProgress` shared by several downloads.
+///
+/// When `expected` is given the file is removed and an error returned if the digest differs.
+/// The computed digest is returned either way.
+pub async fn download(url: &str, path: &str, expected: Option<&Checksum>, pb: &ProgressBar) -> Result<Checksum, ApiError> {
+    if static_index::is_local(url) {
+        return copy_local(url, path, expected, pb);
+    }
+    with_retries(Some(pb), || download_once(url, path, expected, pb)).await
+}
+
+/// Copies an archive referenced by a `file://` URL, hashing it on the way like a download.
+fn copy_local(url: &str, path: &str, expected: Option<&Checksum>, pb: &ProgressBar) -> Result<Checksum, ApiError> {
+    let source = static_index::to_path(url);
+    let mut reader = File::open(&source).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => ApiError::NotFound(format!("{} does not exist", url)),
+        _ => ApiError::Io(e),
+    })?;
+
+    if let Some(parent) = Path::new(path).parent()
+        && !parent.exists()
+    {
+        fs::create_dir_all(parent)?;
+    }
+
+    pb.set_length(reader.metadata()?.len());
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{prefix} {wide_bar} {bytes}/{total_bytes}")
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
+            .progress_chars("##-")
+    );
+
+    let mut hasher = Hasher::new(expected.map(|c| c.algorithm).unwrap_or(ChecksumAlgorithm::Sha256));
+    let mut file = File::create(path)?;
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
         }
+        file.write_all(&buffer[..read])?;
+        hasher.update(&buffer[..read]);
+        pb.inc(read as u64);
     }
-    
-    let client = reqwest::Client::new();
-    let response = client.get(url).send().await?;
-    
-    if !response.status().is_success() {
-        return Err(Box::from(format!("Failed to download: {}", response.status())));
+    pb.finish_with_message("Download complete!");
+    drop(file);
+
+    let actual = hasher.finalize();
+    if let Some(expected) = expected
+        && let Err(e) = checksum::verify(url, expected, &actual)
+    {
+        let _ = fs::remove_file(path);
+        return Err(e);
+    }
+    Ok(actual)
+}
+
+/// Tries each of `urls` in turn until one downloads and verifies.
+///
+/// Returns the URL that succeeded along with the computed digest. Local problems (such as a
+/// file that cannot be written) stop immediately since another mirror would not help.
+pub async fn download_from_mirrors(urls: &[String], path: &str, expected: Option<&Checksum>, pb: &ProgressBar) -> Result<(String, Checksum), ApiError> {
+    let mut last_error = ApiError::NotFound("No download URL available".to_string());
+
+    for (i, url) in urls.iter().enumerate() {
+        match download(url, path, expected, pb).await {
+            Ok(checksum) => return Ok((url.clone(), checksum)),
+            Err(e @ (ApiError::Io(_) | ApiError::Config(_))) => return Err(e),
+            Err(e) => {
+                if i + 1 < urls.len() {
+                    pb.suspend(|| print_message(MessageType::Warning(&format!("{} - trying the next mirror", e))));
+                }
+                last_error = e;
+            }
+        }
+    }
+
+    Err(last_error)
+}
+
+async fn download_once(url: &str, path: &str, expected: Option<&Checksum>, pb: &ProgressBar) -> Result<Checksum, ApiError> {
+
+    if let Some(parent) = Path::new(path).parent()
+        && !parent.exists()
+    {
+        fs::create_dir_all(parent)?;
+    }
+
+    let (part_path, meta_path) = partial_paths(url)?;
+    let validator = read_validator(&meta_path);
+    let existing = match (&validator, fs::metadata(&part_path)) {
+        (Some(_), Ok(metadata)) => metadata.len(),
+        _ => 0,
+    };
+
+    let mut response = match &validator {
+        Some(validator) if existing > 0 => send_download_request(url, Some((existing, validator))).await?,
+        _ => send_download_request(url, None).await?,
+    };
+    if response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        remove_partial(&part_path, &meta_path);
+        response = send_download_request(url, None).await?;
     }
+    let response = check_status(url, response)?;
+    let resuming = response.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+
+    let mut hasher = Hasher::new(expected.map(|c| c.algorithm).unwrap_or(ChecksumAlgorithm::Sha256));
+    let mut downloaded = 0;
+    let mut file = if resuming {
+        io::copy(&mut File::open(&part_path)?, &mut hasher)?;
+        downloaded = existing;
+        OpenOptions::new().append(true).open(&part_path)?
+    } else {
+        match get_validator(&response) {
+            Some(validator) => write_validator(&meta_path, url, &validator),
+            None => {
+                let _ = fs::remove_file(&meta_path);
+            }
+        }
+        File::create(&part_path)?
+    };
     
-    let total_size = response
+    let total_size = downloaded + response
         .content_length()
         .unwrap_or(0);
     
-    let pb = ProgressBar::new(total_size);
+    if resuming {
+        // Read the prefix first: `suspend` holds the bar's lock while the closure runs.
+        let prefix = pb.prefix();
+        pb.suspend(|| println!("Resuming {} at {} bytes", prefix, existing));
+    }
+
+    pb.set_length(total_size);
     pb.set_style(
         ProgressStyle::default_bar()
-            .template("{wide_bar} {bytes}/{total_bytes} ({eta})")? 
+            .template("{prefix} {wide_bar} {bytes}/{total_bytes} ({eta})")
+            .unwrap_or_else(|_| ProgressStyle::default_bar())
             .progress_chars("##-")
     );
-    
-    let mut file = File::create(path)?;
-    let mut downloaded = 0;
+    pb.set_position(downloaded);
     
     let mut stream = response.bytes_stream();
     while let Some(chunk_result) = stream.next().await {
-        let chunk = chunk_result?;
+        let chunk = match chunk_result {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                pb.abandon();
+                return Err(ApiError::network(url, e.without_url()));
+            }
+        };
         file.write_all(&chunk)?;
+        hasher.update(&chunk);
         downloaded += chunk.len() as u64;
         pb.set_position(downloaded);
     }
     
     pb.finish_with_message("Download complete!");
-    Ok(())
+    drop(file);
+
+    let actual = hasher.finalize();
+    if let Some(expected) = expected
+        && let Err(e) = checksum::verify(url, expected, &actual)
+    {
+        remove_partial(&part_path, &meta_path);
+        return Err(e);
+    }
+
+    if fs::rename(&part_path, path).is_err() {
+        fs::copy(&part_path, path)?;
+        fs::remove_file(&part_path)?;
+    }
+    let _ = fs::remove_file(&meta_path);
+
+    Ok(actual)
 }
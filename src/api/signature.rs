@@ -0,0 +1,51 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ed25519_dalek::{Signature, VerifyingKey};
+
+use super::error::ApiError;
+
+/// Response header carrying the base64 ed25519 signature of the body.
+pub const SIGNATURE_HEADER: &str = "x-pkit-signature";
+
+fn decode_key(key: &str) -> Option<VerifyingKey> {
+    let bytes: [u8; 32] = STANDARD.decode(key.trim()).ok()?.try_into().ok()?;
+    VerifyingKey::from_bytes(&bytes).ok()
+}
+
+/// The bytes a registry signs for the document at `path` (such as `language/node`): the path,
+/// a newline, then the body. Binding the path stops a signed document from being served in
+/// answer to a different request.
+fn signed_payload(path: &str, body: &str) -> Vec<u8> {
+    format!("{}\n{}", path, body).into_bytes()
+}
+
+/// Checks `body`, fetched for `path`, against the registry's trusted keys.
+///
+/// Registries without trusted keys are not verified. Otherwise the signature must be
+/// present and valid for at least one of the keys.
+pub fn verify(url: &str, path: &str, body: &str, signature: Option<&str>, trusted_keys: &[String]) -> Result<(), ApiError> {
+    if trusted_keys.is_empty() {
+        return Ok(());
+    }
+
+    let Some(signature) = signature else {
+        return Err(ApiError::signature(url, "the registry did not sign this response"));
+    };
+
+    let signature = STANDARD
+        .decode(signature.trim())
+        .ok()
+        .and_then(|bytes| Signature::from_slice(&bytes).ok())
+        .ok_or_else(|| ApiError::signature(url, "the signature is malformed"))?;
+
+    let keys: Vec<VerifyingKey> = trusted_keys.iter().filter_map(|key| decode_key(key)).collect();
+    if keys.is_empty() {
+        return Err(ApiError::signature(url, "none of the trusted keys for this registry are valid ed25519 keys"));
+    }
+
+    let payload = signed_payload(path, body);
+    if keys.iter().any(|key| key.verify_strict(&payload, &signature).is_ok()) {
+        Ok(())
+    } else {
+        Err(ApiError::signature(url, "the signature does not match any trusted key"))
+    }
+}
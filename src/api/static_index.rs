@@ -0,0 +1,71 @@
+use std::io;
+use std::path::PathBuf;
+
+use crate::filesystem::{self, config::Registry};
+
+use super::cache::{self, Fetched};
+use super::error::ApiError;
+
+/// Whether `url` points at the local filesystem (`file://` URL or plain path).
+pub fn is_local(url: &str) -> bool {
+    !url.starts_with("http://") && !url.starts_with("https://")
+}
+
+/// Converts a `file://` URL (or plain path) into a filesystem path.
+pub fn to_path(url: &str) -> PathBuf {
+    let path = url.strip_prefix("file://").unwrap_or(url);
+
+    // file:///C:/pkit -> C:/pkit
+    let bytes = path.as_bytes();
+    if bytes.len() > 2 && bytes[0] == b'/' && bytes[2] == b':' && bytes[1].is_ascii_alphabetic() {
+        return PathBuf::from(&path[1..]);
+    }
+    PathBuf::from(path)
+}
+
+fn read_local(url: &str) -> Result<String, ApiError> {
+    filesystem::read(&to_path(url)).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => ApiError::NotFound(format!("{} does not exist", url)),
+        _ => ApiError::Io(e),
+    })
+}
+
+/// Reads one file of a static index together with its detached `<file>.sig`, if any.
+async fn fetch_file(url: &str) -> Result<Fetched, ApiError> {
+    let signature_url = format!("{}.sig", url);
+
+    if is_local(url) {
+        let body = read_local(url)?;
+        let signature = read_local(&signature_url).ok();
+        return Ok(Fetched { body, signature });
+    }
+
+    let fetched = cache::fetch(url).await?;
+    let signature = match cache::fetch(&signature_url).await {
+        Ok(signature) => Some(signature.body),
+        Err(e) if e.is_not_found() => None,
+        Err(e) => return Err(e),
+    };
+    Ok(Fetched { body: fetched.body, signature })
+}
+
+/// Fetches `path` (such as `language/node`) from a static registry.
+///
+/// Each endpoint of the HTTP API maps to `<path>.json`, or to `<path>/index.json` for
+/// layouts that keep every language in its own directory. Returns the URL that was read.
+pub async fn fetch(registry: &Registry, path: &str) -> Result<(String, Fetched), ApiError> {
+    let candidates = [
+        format!("{}/{}.json", registry.url, path),
+        format!("{}/{}/index.json", registry.url, path),
+    ];
+
+    for url in &candidates {
+        match fetch_file(url).await {
+            Ok(fetched) => return Ok((url.clone(), fetched)),
+            Err(e) if e.is_not_found() => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(ApiError::NotFound(format!("{}/{} does not exist", registry.url, path)))
+}
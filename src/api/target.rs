@@ -0,0 +1,239 @@
+use std::env::consts::{ARCH, OS};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// Platform and architecture to install for, when not this machine (`--platform`/`--arch`).
+static TARGET_OVERRIDE: OnceLock<(String, String)> = OnceLock::new();
+
+/// Platform name the registry uses for this machine.
+pub fn get_host_platform() -> &'static str {
+    match OS {
+        "windows" => "win",
+        "macos" => "darwin",
+        "linux" => "linux",
+        _ => "",
+    }
+}
+
+/// Architecture name the registry uses for this machine.
+pub fn get_host_arch() -> &'static str {
+    if ARCH == "x86_64" { "x64" }
+    else if (ARCH == "arm" && cfg!(target_pointer_width = "64")) || ARCH == "aarch64" { "arm64" }
+    else { "none" }
+}
+
+/// Maps common spellings (`windows`, `macos`, ...) to the registry's platform names.
+pub fn normalize_platform(platform: &str) -> Option<&'static str> {
+    match platform.to_lowercase().as_str() {
+        "win" | "windows" | "win32" => Some("win"),
+        "darwin" | "macos" | "mac" | "osx" => Some("darwin"),
+        "linux" => Some("linux"),
+        _ => None,
+    }
+}
+
+/// Maps common spellings (`amd64`, `aarch64`, ...) to the registry's architecture names.
+pub fn normalize_arch(arch: &str) -> Option<&'static str> {
+    match arch.to_lowercase().as_str() {
+        "x64" | "amd64" | "x86_64" => Some("x64"),
+        "arm64" | "aarch64" => Some("arm64"),
+        _ => None,
+    }
+}
+
+/// Targets another platform and/or architecture for the rest of the process.
+pub fn set_target_override(platform: Option<&str>, arch: Option<&str>) -> Result<(), String> {
+    let platform = match platform {
+        Some(platform) => normalize_platform(platform)
+            .ok_or_else(|| format!("Unknown platform '{}' (expected win, darwin or linux)", platform))?,
+        None => get_host_platform(),
+    };
+    let arch = match arch {
+        Some(arch) => normalize_arch(arch).ok_or_else(|| format!("Unknown architecture '{}' (expected x64 or arm64)", arch))?,
+        None => get_host_arch(),
+    };
+
+    let _ = TARGET_OVERRIDE.set((platform.to_string(), arch.to_string()));
+    Ok(())
+}
+
+/// Platform that versions are looked up and installed for.
+pub fn get_platform() -> &'static str {
+    TARGET_OVERRIDE.get().map(|(platform, _)| platform.as_str()).unwrap_or_else(get_host_platform)
+}
+
+/// Architecture that versions are looked up and installed for.
+pub fn get_arch() -> &'static str {
+    TARGET_OVERRIDE.get().map(|(_, arch)| arch.as_str()).unwrap_or_else(get_host_arch)
+}
+
+/// Whether an x64 binary can run on this arm64 machine, and what runs it.
+fn detect_x64_emulator() -> Option<&'static str> {
+    if get_host_arch() != "arm64" {
+        return None;
+    }
+
+    match OS {
+        "macos" if Path::new("/Library/Apple/usr/libexec/oah/libRosettaRuntime").exists() => Some("Rosetta 2"),
+        // Windows 11 on Arm runs x64 binaries out of the box.
+        "windows" => Some("Windows x64 emulation"),
+        "linux" => {
+            // qemu-user-static, box64 and FEX register a binfmt_misc handler for x86-64 binaries.
+            let handlers = fs::read_dir("/proc/sys/fs/binfmt_misc").ok()?;
+            let registered = handlers.flatten().any(|entry| {
+                let name = entry.file_name().to_string_lossy().to_lowercase();
+                (name.contains("x86_64") || name.contains("x86-64") || name.contains("box64") || name.contains("fex"))
+                    && fs::read_to_string(entry.path()).is_ok_and(|content| content.starts_with("enabled"))
+            });
+            registered.then_some("binfmt_misc")
+        }
+        _ => None,
+    }
+}
+
+/// The emulator that lets x64 builds stand in for missing native arm64 builds, if any.
+///
+/// Only applies when installing for this machine; an explicit `--arch` or `--platform`
+/// is always honoured as given.
+pub fn get_fallback_emulator() -> Option<&'static str> {
+    if is_cross() {
+        return None;
+    }
+    static EMULATOR: OnceLock<Option<&'static str>> = OnceLock::new();
+    *EMULATOR.get_or_init(detect_x64_emulator)
+}
+
+/// Architecture to fall back to when no native build exists.
+pub const FALLBACK_ARCH: &str = "x64";
+
+/// Whether `platform`/`arch` describe this machine.
+pub fn is_host(platform: &str, arch: &str) -> bool {
+    platform == get_host_platform() && arch == get_host_arch()
+}
+
+/// Whether this process installs for another machine.
+pub fn is_cross() -> bool {
+    !is_host(get_platform(), get_arch())
+}
+
+/// The C library of a Linux system; builds linked against one don't run on the other.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Libc {
+    /// glibc with its `(major, minor)` version, when it could be determined.
+    Glibc(Option<(u32, u32)>),
+    Musl,
+}
+
+impl Libc {
+    /// Name used by registries: `glibc` or `musl`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Libc::Glibc(_) => "glibc",
+            Libc::Musl => "musl",
+        }
+    }
+}
+
+impl fmt::Display for Libc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Libc::Glibc(Some((major, minor))) => write!(f, "glibc {}.{}", major, minor),
+            libc => write!(f, "{}", libc.name()),
+        }
+    }
+}
+
+/// Parses a glibc version such as `2.35` into `(major, minor)`.
+pub fn parse_glibc_version(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.trim().split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().map(|minor| minor.parse().ok()).unwrap_or(Some(0))?;
+    Some((major, minor))
+}
+
+/// The program interpreter (dynamic loader) named in the `PT_INTERP` header of an ELF file,
+/// such as `/lib64/ld-linux-x86-64.so.2` or `/lib/ld-musl-x86_64.so.1`.
+fn read_elf_interpreter(path: &str) -> Option<String> {
+    let data = fs::read(path).ok()?;
+    if data.get(..4)? != b"\x7fELF" {
+        return None;
+    }
+    let is_64 = *data.get(4)? == 2;
+    let little_endian = *data.get(5)? == 1;
+
+    let read = |offset: usize, size: usize| -> Option<u64> {
+        let bytes = data.get(offset..offset + size)?;
+        let mut value = 0u64;
+        for i in 0..size {
+            let byte = if little_endian { bytes[size - 1 - i] } else { bytes[i] };
+            value = (value << 8) | u64::from(byte);
+        }
+        Some(value)
+    };
+
+    // Offsets of e_phoff, e_phentsize and e_phnum, and of p_offset and p_filesz in a program header.
+    let (phoff, phentsize, phnum, p_offset, p_filesz, word) =
+        if is_64 { (0x20, 0x36, 0x38, 0x08, 0x20, 8) } else { (0x1c, 0x2a, 0x2c, 0x04, 0x10, 4) };
+    let table = read(phoff, word)? as usize;
+    let entry_size = read(phentsize, 2)? as usize;
+    let count = read(phnum, 2)? as usize;
+
+    const PT_INTERP: u64 = 3;
+    (0..count).find_map(|i| {
+        let header = table + i * entry_size;
+        if read(header, 4)? != PT_INTERP {
+            return None;
+        }
+        let start = read(header + p_offset, word)? as usize;
+        let length = read(header + p_filesz, word)? as usize;
+        let name = data.get(start..start + length)?;
+        Some(String::from_utf8_lossy(name).trim_end_matches('\0').to_string())
+    })
+}
+
+fn detect_libc() -> Option<Libc> {
+    if OS != "linux" {
+        return None;
+    }
+
+    // `getconf GNU_LIBC_VERSION` prints "glibc 2.35"; `ldd --version` starts with "ldd (GNU libc) 2.35".
+    let output = |program: &str, arg: &str| {
+        Command::new(program)
+            .arg(arg)
+            .output()
+            .ok()
+            .map(|output| format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr)))
+    };
+
+    // The loader the system's own shell runs under decides; a stray musl loader from an
+    // installed `musl` package doesn't make a glibc system musl.
+    let is_musl = match read_elf_interpreter("/bin/sh") {
+        Some(interpreter) => interpreter.contains("ld-musl"),
+        // A statically linked shell has no loader; ask the system's ldd instead.
+        None => output("ldd", "--version").is_some_and(|text| text.to_lowercase().contains("musl")),
+    };
+    if is_musl {
+        return Some(Libc::Musl);
+    }
+
+    // getconf may run and still fail (e.g. print an error), so fall back on a failed parse.
+    let parse = |text: String| text.lines().next().and_then(|line| line.split_whitespace().last()).and_then(parse_glibc_version);
+    let version = output("getconf", "GNU_LIBC_VERSION")
+        .and_then(parse)
+        .or_else(|| output("ldd", "--version").and_then(parse));
+    Some(Libc::Glibc(version))
+}
+
+/// The libc of this machine, or `None` when it is not Linux.
+pub fn get_host_libc() -> Option<Libc> {
+    static HOST_LIBC: OnceLock<Option<Libc>> = OnceLock::new();
+    *HOST_LIBC.get_or_init(detect_libc)
+}
+
+/// The libc to select builds for: the host's, unless targeting another platform.
+pub fn get_libc() -> Option<Libc> {
+    if get_platform() == get_host_platform() { get_host_libc() } else { None }
+}
@@ -1,21 +1,48 @@
 use pkit::filesystem::config::Config;
+use pkit::api;
 use pkit::cli::{Cli, Commands};
-use pkit::commands::{list, install, default, uninstall, switch, path};
+use pkit::formatter::{print_message, MessageType};
+use pkit::commands::{list, install, outdated, upgrade, default, uninstall, switch, alias, current, env, path, cache};
 
 // PATH="$(pwd):$PATH"
 
+fn set_target(platform: Option<&str>, arch: Option<&str>) {
+    if let Err(e) = api::set_target_override(platform, arch) {
+        print_message(MessageType::Error(&e));
+        std::process::exit(1);
+    }
+}
+
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse_args();
 
     let _ = Config::new();
 
+    if !cli.registries.is_empty() {
+        api::set_registry_override(&cli.registries);
+    }
+
     match &cli.command {
-        Commands::List { language, installed } => {
+        Commands::List { language, installed, refresh, platform, arch } => {
+            api::cache::set_refresh(*refresh);
+            set_target(platform.as_deref(), arch.as_deref());
             list::handle_list_command(language.as_ref(), *installed).await;
         }
-        Commands::Install { language, version } => {
-            install::handle_install_command(language, version).await;
+        Commands::Install { packages, default, no_default, refresh, platform, arch } => {
+            api::cache::set_refresh(*refresh);
+            set_target(platform.as_deref(), arch.as_deref());
+            let default = if *default { Some(true) } else if *no_default { Some(false) } else { None };
+            install::handle_install_command(packages, default).await;
+        }
+        Commands::Outdated { language, refresh } => {
+            api::cache::set_refresh(*refresh);
+            outdated::handle_outdated_command(language.as_deref()).await;
+        }
+        Commands::Upgrade { language, major, remove, keep, refresh } => {
+            api::cache::set_refresh(*refresh);
+            let remove = if *remove { Some(true) } else if *keep { Some(false) } else { None };
+            upgrade::handle_upgrade_command(language.as_deref(), *major, remove).await;
         }
         Commands::Default { language, version, show } => {
             default::handle_default_command(language, version.as_ref(), *show);
@@ -26,8 +53,20 @@ async fn main() {
         Commands::Switch { language, version } => {
             switch::handle_switch_command(language, version);
         }
+        Commands::Alias { language, name, version, remove } => {
+            alias::handle_alias_command(language, name.as_deref(), version.as_deref(), *remove);
+        }
+        Commands::Current { language } => {
+            current::handle_current_command(language.as_deref());
+        }
+        Commands::Env => {
+            env::handle_env_command();
+        }
         Commands::Path { action, name, path } => {
             path::handle_path_command(action, name.as_deref(), path.as_deref());
         }
+        Commands::Cache { action, target } => {
+            cache::handle_cache_command(action, target.as_deref());
+        }
     }
 }
\ No newline at end of file
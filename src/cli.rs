@@ -19,6 +19,9 @@ fn get_custom_styles() -> styling::Styles {
 #[command(version = "0.0.2")]
 #[command(styles = get_custom_styles())]
 pub struct Cli {
+    /// Registry URL to use instead of the configured ones (can be repeated)
+    #[arg(long = "registry", global = true, value_name = "URL", help = colorize("&bRegistry URL to use instead of the configured ones (can be repeated)&r"))]
+    pub registries: Vec<String>,
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -34,16 +37,66 @@ pub enum Commands {
         /// List only installed packages
         #[arg(long, help = colorize("&bList only installed packages&r"))]
         installed: bool,
+        /// Ignore cached registry data and fetch it again
+        #[arg(long, help = colorize("&bIgnore cached registry data and fetch it again&r"))]
+        refresh: bool,
+        /// Platform to target instead of this machine (win, darwin, linux)
+        #[arg(long, value_name = "PLATFORM", help = colorize("&bPlatform to target instead of this machine (win, darwin, linux)&r"))]
+        platform: Option<String>,
+        /// Architecture to target instead of this machine (x64, arm64)
+        #[arg(long, value_name = "ARCH", help = colorize("&bArchitecture to target instead of this machine (x64, arm64)&r"))]
+        arch: Option<String>,
     },
-    /// Install a language and version
-    #[command(about = colorize("&aInstall a language and version&r"))]
+    /// Install one or more language versions
+    #[command(about = colorize("&aInstall one or more language versions&r"))]
     Install {
-        /// Language to install
-        #[arg(help = colorize("&eLanguage to install&r"))]
-        language: String,
-        /// Version to install
-        #[arg(help = colorize("&eVersion to install&r"))]
-        version: String,
+        /// Packages to install, as `language@version` or `language version`
+        #[arg(required = true, value_name = "PACKAGES", help = colorize("&ePackages to install, as language@version or language version&r"))]
+        packages: Vec<String>,
+        /// Set the installed versions as default without asking
+        #[arg(long, conflicts_with = "no_default", help = colorize("&bSet the installed versions as default without asking&r"))]
+        default: bool,
+        /// Do not set the installed versions as default and do not ask
+        #[arg(long, help = colorize("&bDo not set the installed versions as default and do not ask&r"))]
+        no_default: bool,
+        /// Ignore cached registry data and fetch it again
+        #[arg(long, help = colorize("&bIgnore cached registry data and fetch it again&r"))]
+        refresh: bool,
+        /// Platform to target instead of this machine (win, darwin, linux)
+        #[arg(long, value_name = "PLATFORM", help = colorize("&bPlatform to target instead of this machine (win, darwin, linux)&r"))]
+        platform: Option<String>,
+        /// Architecture to target instead of this machine (x64, arm64)
+        #[arg(long, value_name = "ARCH", help = colorize("&bArchitecture to target instead of this machine (x64, arm64)&r"))]
+        arch: Option<String>,
+    },
+    /// Show installed versions that have newer releases
+    #[command(about = colorize("&aShow installed versions that have newer releases&r"))]
+    Outdated {
+        /// Language to check (all installed languages when omitted)
+        #[arg(help = colorize("&eLanguage to check (all installed languages when omitted)&r"))]
+        language: Option<String>,
+        /// Ignore cached registry data and fetch it again
+        #[arg(long, help = colorize("&bIgnore cached registry data and fetch it again&r"))]
+        refresh: bool,
+    },
+    /// Upgrade installed versions to the newest release of their line
+    #[command(about = colorize("&aUpgrade installed versions to the newest release of their line&r"))]
+    Upgrade {
+        /// Language to upgrade (all installed languages when omitted)
+        #[arg(help = colorize("&eLanguage to upgrade (all installed languages when omitted)&r"))]
+        language: Option<String>,
+        /// Also move to a newer major version
+        #[arg(long, help = colorize("&bAlso move to a newer major version&r"))]
+        major: bool,
+        /// Remove the versions that were upgraded from without asking
+        #[arg(long, conflicts_with = "keep", help = colorize("&bRemove the versions that were upgraded from without asking&r"))]
+        remove: bool,
+        /// Keep the versions that were upgraded from and do not ask
+        #[arg(long, help = colorize("&bKeep the versions that were upgraded from and do not ask&r"))]
+        keep: bool,
+        /// Ignore cached registry data and fetch it again
+        #[arg(long, help = colorize("&bIgnore cached registry data and fetch it again&r"))]
+        refresh: bool,
     },
     /// Set default language
     #[command(about = colorize("&aSet default language&r"))]
@@ -81,6 +134,32 @@ pub enum Commands {
         #[arg(help = colorize("&eVersion to switch to&r"))]
         version: String,
     },
+    /// Name an installed version so it can be used in place of a version
+    #[command(about = colorize("&aName an installed version so it can be used in place of a version&r"))]
+    Alias {
+        /// Language the alias belongs to
+        #[arg(help = colorize("&eLanguage the alias belongs to&r"))]
+        language: String,
+        /// Name of the alias (lists the aliases when omitted)
+        #[arg(help = colorize("&eName of the alias (lists the aliases when omitted)&r"))]
+        name: Option<String>,
+        /// Version the alias points to (shows the alias when omitted)
+        #[arg(help = colorize("&eVersion the alias points to (shows the alias when omitted)&r"))]
+        version: Option<String>,
+        /// Remove the alias
+        #[arg(long, conflicts_with = "version", help = colorize("&bRemove the alias&r"))]
+        remove: bool,
+    },
+    /// Show the active version of each language and where it comes from
+    #[command(about = colorize("&aShow the active version of each language and where it comes from&r"))]
+    Current {
+        /// Language to show (all languages when omitted)
+        #[arg(help = colorize("&eLanguage to show (all languages when omitted)&r"))]
+        language: Option<String>,
+    },
+    /// Print shell commands that activate the versions for the current directory
+    #[command(about = colorize("&aPrint shell commands that activate the versions for the current directory&r"))]
+    Env,
     /// Manage path sources for custom installations
     #[command(about = colorize("&aManage path sources for custom installations&r"))]
     Path {
@@ -94,6 +173,16 @@ pub enum Commands {
         #[arg(help = colorize("&ePath to the source&r"))]
         path: Option<String>,
     },
+    /// Inspect and clean the download and metadata cache
+    #[command(about = colorize("&aInspect and clean the download and metadata cache&r"))]
+    Cache {
+        /// Action to perform (list, size, clean)
+        #[arg(help = colorize("&eAction to perform (list, size, clean)&r"))]
+        action: String,
+        /// Section to clean (archives, metadata, downloads, all)
+        #[arg(help = colorize("&eSection to clean (archives, metadata, downloads, all)&r"))]
+        target: Option<String>,
+    },
 }
 
 impl Cli {
@@ -4,4 +4,10 @@ pub mod install;
 pub mod default;
 pub mod uninstall;
 pub mod switch;
-pub mod path;
\ No newline at end of file
+pub mod path;
+pub mod cache;
+pub mod alias;
+pub mod outdated;
+pub mod upgrade;
+pub mod current;
+pub mod env;
\ No newline at end of file
@@ -0,0 +1,91 @@
+use crate::filesystem::config::Config;
+use crate::formatter::{
+    capitalize_first, colorize, print_message, print_table_footer, print_table_header, print_table_row, MessageType,
+};
+use crate::resolver;
+
+pub fn handle_alias_command(language: &str, name: Option<&str>, version: Option<&str>, remove: bool) {
+    let mut config = Config::new();
+
+    let Some(name) = name else {
+        list_aliases(&config, language);
+        return;
+    };
+
+    if remove {
+        if config.get_alias(language, name).is_some() {
+            config.remove_alias(language, name);
+            print_message(MessageType::Success(&format!("Removed alias '{}' for {}", name, capitalize_first(language))));
+        } else {
+            print_message(MessageType::Warning(&format!("Alias '{}' not found for {}", name, capitalize_first(language))));
+        }
+        return;
+    }
+
+    let Some(request) = version else {
+        match config.get_alias(language, name) {
+            Some(alias) => print_message(MessageType::Info(&format!("'{}' points to {} {}", name, capitalize_first(language), alias.version))),
+            None => print_message(MessageType::Warning(&format!("Alias '{}' not found for {}", name, capitalize_first(language)))),
+        }
+        return;
+    };
+
+    if !resolver::is_alias_name(name) {
+        print_message(MessageType::Error(&format!(
+            "'{}' can't be used as an alias; names start with a letter and must not look like a version or keyword",
+            name
+        )));
+        std::process::exit(1);
+    }
+
+    let version = match resolver::resolve_installed(&config, language, request) {
+        Ok(Some(installed)) => {
+            if installed.version != request {
+                print_message(MessageType::Info(&format!("Resolved {} {} to {}", capitalize_first(language), request, installed.version)));
+            }
+            installed.version.clone()
+        }
+        Ok(None) => {
+            print_message(MessageType::Error(&format!(
+                "{} {} is not installed; install it first with 'pkit install {} {}'",
+                capitalize_first(language),
+                request,
+                language,
+                request
+            )));
+            std::process::exit(1);
+        }
+        Err(e) => {
+            print_message(MessageType::Error(&e));
+            std::process::exit(1);
+        }
+    };
+
+    config.set_alias(language, name, &version);
+    print_message(MessageType::Success(&format!("'{}' now points to {} {}", name, capitalize_first(language), version)));
+}
+
+fn list_aliases(config: &Config, language: &str) {
+    let aliases: Vec<_> = config.aliases.iter().filter(|alias| alias.language == language).collect();
+    if aliases.is_empty() {
+        print_message(MessageType::Info(&format!("No aliases configured for {}", capitalize_first(language))));
+        println!("{}", colorize(&format!("&6Usage: &fpkit alias {} &e<name> <version>&r", language)));
+        return;
+    }
+
+    println!("{}", colorize(&format!("&b{} Aliases:&r", capitalize_first(language))));
+    println!();
+
+    let columns = [("Alias", 20), ("Version", 20), ("Status", 12)];
+    print_table_header(&columns);
+
+    for alias in aliases {
+        let name_colored = format!("&a{}&r", alias.name);
+        let version_colored = format!("&e{}&r", alias.version);
+        let status = if config.get(language, &alias.version).is_some() { "&8Installed&r" } else { "&cMissing&r" };
+        let values = [name_colored.as_str(), version_colored.as_str(), status];
+        print_table_row(&columns, &values);
+    }
+
+    print_table_footer(&columns);
+}
@@ -0,0 +1,121 @@
+use crate::api::{archive, cache, request};
+use crate::filesystem;
+use crate::formatter::{
+    print_message, MessageType,
+    print_table_header, print_table_row, print_table_footer, colorize
+};
+use indicatif::HumanBytes;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The parts of the cache directory that `pkit cache` knows how to report on and clean.
+const CATEGORIES: [&str; 3] = ["archives", "metadata", "downloads"];
+
+fn get_category_dir(category: &str) -> io::Result<PathBuf> {
+    match category {
+        "archives" => archive::get_archive_cache_dir(),
+        "metadata" => cache::get_metadata_cache_dir(),
+        _ => request::get_partial_download_dir(),
+    }
+}
+
+fn count_entries(dir: &Path) -> usize {
+    fs::read_dir(dir).map(|entries| entries.count()).unwrap_or(0)
+}
+
+pub fn handle_cache_command(action: &str, target: Option<&str>) {
+    match action {
+        "list" => list_cached_archives(),
+        "size" => print_cache_size(),
+        "clean" => {
+            let categories: Vec<&str> = match target {
+                None | Some("all") => CATEGORIES.to_vec(),
+                Some(category) if CATEGORIES.contains(&category) => vec![category],
+                Some(category) => {
+                    print_message(MessageType::Error(&format!("Unknown cache section: '{}'. Use 'archives', 'metadata', 'downloads' or 'all'", category)));
+                    std::process::exit(1);
+                }
+            };
+            clean_cache(&categories);
+        }
+        _ => {
+            print_message(MessageType::Error(&format!("Unknown action: '{}'. Use 'list', 'size', or 'clean'", action)));
+            println!("{}", colorize("&6Usage:&r"));
+            println!("{}", colorize("  &fpkit cache list&8                  &7List cached archives&r"));
+            println!("{}", colorize("  &fpkit cache size&8                  &7Show how much space the cache uses&r"));
+            println!("{}", colorize("  &fpkit cache clean &8[&esection&8]      &7Remove archives, metadata, downloads or all&r"));
+            std::process::exit(1);
+        }
+    }
+}
+
+fn list_cached_archives() {
+    let mut archives = archive::list();
+    if archives.is_empty() {
+        print_message(MessageType::Info("No archives are cached"));
+        return;
+    }
+    archives.sort_by_key(|cached| std::cmp::Reverse(cached.cached_at));
+
+    println!("{}", colorize("&bCached Archives:&r"));
+    println!();
+
+    let columns = [("Archive", 44), ("Size", 12), ("Checksum", 12)];
+    print_table_header(&columns);
+
+    for cached in &archives {
+        let name = cached.path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+        let name_colored = format!("&e{}&r", name);
+        let size_colored = format!("&3{}&r", HumanBytes(cached.size));
+        let checksum_colored = if cached.verified { "&aVerified&r" } else { "&8Unverified&r" };
+        let values = [name_colored.as_str(), size_colored.as_str(), checksum_colored];
+        print_table_row(&columns, &values);
+    }
+
+    print_table_footer(&columns);
+}
+
+fn print_cache_size() {
+    let columns = [("Section", 20), ("Entries", 10), ("Size", 14)];
+    print_table_header(&columns);
+
+    let mut total = 0;
+    for category in CATEGORIES {
+        let dir = get_category_dir(category).unwrap_or_default();
+        let size = filesystem::dir_size(&dir);
+        total += size;
+
+        let name_colored = format!("&e{}&r", category);
+        let count = count_entries(&dir).to_string();
+        let size_colored = format!("&3{}&r", HumanBytes(size));
+        let values = [name_colored.as_str(), count.as_str(), size_colored.as_str()];
+        print_table_row(&columns, &values);
+    }
+
+    let total_colored = format!("&a{}&r", HumanBytes(total));
+    print_table_row(&columns, &["&ftotal&r", "", total_colored.as_str()]);
+    print_table_footer(&columns);
+}
+
+fn clean_cache(categories: &[&str]) {
+    let mut freed = 0;
+
+    for category in categories {
+        let Ok(dir) = get_category_dir(category) else {
+            continue;
+        };
+        if !dir.exists() {
+            continue;
+        }
+
+        let size = filesystem::dir_size(&dir);
+        if let Err(e) = fs::remove_dir_all(&dir) {
+            print_message(MessageType::Error(&format!("Failed to remove {:?}: {}", dir, e)));
+            std::process::exit(1);
+        }
+        freed += size;
+    }
+
+    print_message(MessageType::Success(&format!("Removed {} of cached data ({})", HumanBytes(freed), categories.join(", "))));
+}
@@ -0,0 +1,82 @@
+use crate::filesystem::config::Config;
+use crate::formatter::{
+    capitalize_first, colorize, print_box, print_message, print_table_footer, print_table_header, print_table_row,
+    BoxAlignment, BoxOptions, MessageType,
+};
+use crate::project;
+use crate::resolver::{self, Active, ActiveSource};
+
+pub fn handle_current_command(language: Option<&str>) {
+    let config = Config::new();
+
+    let project = match project::find_from_current_dir() {
+        Ok(project) => project,
+        Err(e) => {
+            print_message(MessageType::Error(&e));
+            std::process::exit(1);
+        }
+    };
+
+    let active: Vec<Active> = match language {
+        Some(language) => resolver::resolve_active(&config, project.as_ref(), language).into_iter().collect(),
+        None => resolver::resolve_all_active(&config, project.as_ref()),
+    };
+
+    println!();
+    print_box(&[("&aActive Versions&r", BoxAlignment::Center)], &BoxOptions::default());
+    println!();
+
+    if let Some(project) = &project {
+        println!("{}", colorize(&format!("  &8Project file: &f{}&r", project.path.display())));
+        println!();
+    }
+
+    if active.is_empty() {
+        match language {
+            Some(language) => println!("{}", colorize(&format!("  &8No version of {} is active here.&r", capitalize_first(language)))),
+            None => println!("{}", colorize("&8  No languages are active here.&r")),
+        }
+        println!();
+        return;
+    }
+
+    let columns = [("Language", 14), ("Version", 14), ("Source", 10), ("Status", 14)];
+    print_table_header(&columns);
+
+    for entry in &active {
+        let language_str = format!("&e{}&r", entry.language);
+        let version_str = match entry.installed {
+            Some(installed) => format!("&3{}&r", installed.version),
+            None => format!("&c{}&r", entry.request),
+        };
+        let source_str = match entry.source {
+            ActiveSource::Project(_) => "&5project&r",
+            ActiveSource::Session => "&6session&r",
+            ActiveSource::Default => "&8default&r",
+        };
+        let status_str = if entry.installed.is_some() { "&aInstalled&r" } else { "&cNot installed&r" };
+
+        let values = [language_str.as_str(), version_str.as_str(), source_str, status_str];
+        print_table_row(&columns, &values);
+    }
+
+    print_table_footer(&columns);
+    println!();
+
+    let missing: Vec<String> = active
+        .iter()
+        .filter(|entry| entry.installed.is_none())
+        .map(|entry| format!(" &3pkit install {} {}&r  &8-&r  Install the requested version", entry.language, entry.request))
+        .collect();
+    if !missing.is_empty() {
+        let lines: Vec<(&str, BoxAlignment)> = missing.iter().map(|line| (line.as_str(), BoxAlignment::Left)).collect();
+        print_box(
+            &lines,
+            &BoxOptions {
+                title: Some("Missing Versions"),
+                ..Default::default()
+            },
+        );
+        println!();
+    }
+}
@@ -1,8 +1,9 @@
 use crate::filesystem::config::Config;
 use crate::formatter::{
-    capitalize_first, colorize, print_box, print_table_footer, print_table_header,
-    print_table_row, BoxAlignment, BoxOptions,
+    capitalize_first, colorize, print_box, print_message, print_table_footer, print_table_header,
+    print_table_row, BoxAlignment, BoxOptions, MessageType,
 };
+use crate::resolver;
 
 pub fn handle_default_command(language: &str, version: Option<&String>, show: bool) {
     let mut config = Config::new();
@@ -17,13 +18,24 @@ pub fn handle_default_command(language: &str, version: Option<&String>, show: bo
     }
 
     if let Some(ver) = version {
-        if let Some(installed) = config.get(language, ver) {
+        let installed = match resolver::resolve_installed(&config, language, ver) {
+            Ok(installed) => installed.cloned(),
+            Err(e) => {
+                print_message(MessageType::Error(&e));
+                std::process::exit(1);
+            }
+        };
+
+        if let Some(installed) = installed {
+            if installed.version != *ver {
+                print_message(MessageType::Info(&format!("Resolved {} {} to {}", capitalize_first(language), ver, installed.version)));
+            }
             if installed.default {
-                print_already_default_message(language, ver);
+                print_already_default_message(language, &installed.version);
             } else {
-                config.set_default(language, ver);
+                config.set_default(language, &installed.version);
                 config.write_env_script().expect("Failed to write environment script");
-                print_success_message(language, ver);
+                print_success_message(language, &installed.version);
             }
         } else {
             print_not_installed_message(language, ver);
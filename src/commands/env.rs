@@ -0,0 +1,71 @@
+use crate::filesystem::{config::Config, get_pkit_dir};
+use crate::formatter::capitalize_first;
+use crate::project;
+use crate::resolver::{self, ActiveSource};
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Quotes `value` so the shell takes it literally.
+fn quote(value: &str) -> String {
+    if cfg!(windows) {
+        format!("'{}'", value.replace('\'', "''"))
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+/// Prints a shell command that sets `PATH` so the active version of every language comes first.
+///
+/// Meant for a shell hook such as `eval "$(pkit env)"`, so a project file takes effect when
+/// entering its directory. Earlier pkit entries are dropped from `PATH` before the active ones
+/// are added, so running it again gives the same result and versions from projects you have
+/// left don't linger. Messages go to stderr to keep stdout evaluable.
+pub fn handle_env_command() {
+    let config = Config::new();
+
+    let project = match project::find_from_current_dir() {
+        Ok(project) => project,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut active_paths: Vec<PathBuf> = Vec::new();
+    for active in resolver::resolve_all_active(&config, project.as_ref()) {
+        let Some(installed) = active.installed else {
+            if let ActiveSource::Project(path) = &active.source {
+                eprintln!(
+                    "Warning: {} {} from {} is not installed (pkit install {} {})",
+                    capitalize_first(&active.language),
+                    active.request,
+                    path.display(),
+                    active.language,
+                    active.request
+                );
+            }
+            continue;
+        };
+        active_paths.push(PathBuf::from(&installed.path).join("bin"));
+    }
+
+    let installs_dir = get_pkit_dir().map(|dir| dir.join("bin")).ok();
+    let is_pkit_entry = |entry: &Path| installs_dir.as_ref().is_some_and(|dir| entry.starts_with(dir));
+
+    let current = env::var_os("PATH").unwrap_or_default();
+    let kept = env::split_paths(&current).filter(|entry| !entry.as_os_str().is_empty() && !is_pkit_entry(entry));
+    let path = match env::join_paths(active_paths.into_iter().chain(kept)) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let path = quote(&path.to_string_lossy());
+    if cfg!(windows) {
+        println!("$env:PATH = {}", path);
+    } else {
+        println!("export PATH={}", path);
+    }
+}
@@ -1,115 +1,407 @@
 use crate::{
-    api::{self, request},
-    filesystem::{self, config::Config, get_pkit_dir},
-    formatter::{capitalize_first, colorize, print_box, BoxAlignment, BoxOptions},
+    api::{self, auth, request, target, ApiError},
+    filesystem::{self, config::{ArchFallback, Config, Installed}, get_pkit_dir},
+    formatter::{
+        capitalize_first, colorize, print_box, print_message, print_table_footer,
+        print_table_header, print_table_row, BoxAlignment, BoxOptions, MessageType,
+    },
+    resolver::{self, Candidate},
+    version,
 };
+use futures_util::future::join_all;
+use indicatif::{MultiProgress, ProgressBar};
+use std::io;
 use std::path::PathBuf;
 
-async fn install_software_with_data(software: api::Version) {
+/// Splits install arguments into `(language, version)` pairs.
+///
+/// Accepts `language@version` tokens as well as the older `language version` form, so both
+/// `pkit install node 20.11.0` and `pkit install node@20.11.0 python@3.12.1` work.
+fn parse_install_targets(args: &[String]) -> Result<Vec<(String, String)>, String> {
+    let mut targets = Vec::new();
+    let mut tokens = args.iter();
 
-    println!();
-    let title = format!("&aDownloading &e{} {}&r", capitalize_first(&software.language), software.version);
-    print_box(&[(title.as_str(), BoxAlignment::Center)], &BoxOptions::default());
-    println!();
+    while let Some(token) = tokens.next() {
+        if let Some((language, version)) = token.split_once('@') {
+            if language.is_empty() || version.is_empty() {
+                return Err(format!("'{}' is not in the form language@version", token));
+            }
+            targets.push((language.to_string(), version.to_string()));
+        } else {
+            match tokens.next() {
+                Some(version) if !version.contains('@') => targets.push((token.to_string(), version.to_string())),
+                _ => return Err(format!("Missing version for '{}'", token)),
+            }
+        }
+    }
 
-    let file_name = software.url.split('/').last().unwrap_or("download.tmp");
+    Ok(targets)
+}
 
-    let pkit_dir: PathBuf = get_pkit_dir().expect("Failed to get pkit directory");
-    let archive_path = pkit_dir
+/// Downloads, verifies and extracts one archive, returning the entry to record in the config.
+pub async fn install_archive(software: &api::Version, multi: &MultiProgress) -> Result<Installed, ApiError> {
+    // Cross-installs get their own directory so they can sit next to the native build.
+    let dir_name = if target::is_host(&software.platform, &software.arch) {
+        software.version.clone()
+    } else {
+        format!("{}-{}-{}", software.version, software.platform, software.arch)
+    };
+    let install_dir: PathBuf = get_pkit_dir()?
         .join("bin")
         .join(&software.language)
-        .join(&software.version)
-        .join(file_name);
+        .join(dir_name);
 
-    if let Err(e) = request::download(&software.url, archive_path.to_str().unwrap()).await {
-        println!();
-        print_box(&[("&cDownload Failed&r", BoxAlignment::Center)], &BoxOptions::default());
-        println!();
-        println!("{}", colorize(&format!("  &cError: {}&r", e)));
-        println!();
-        return;
+    let urls = software.download_urls()?;
+    let expected = match &software.checksum {
+        Some(checksum) => Some(checksum.clone()),
+        None if Config::new().discover_checksums => api::checksum::discover(&software.url).await,
+        None => None,
+    };
+
+    let (archive_path, source_url, verified) = if let Some(cached) = api::archive::find(software, expected.as_ref()) {
+        multi.suspend(|| print_message(MessageType::Info(&format!(
+            "Using cached archive for {} {}",
+            capitalize_first(&software.language),
+            software.version
+        ))));
+        let verified = if cached.verified { cached.checksum.map(|c| c.to_string()) } else { None };
+        (cached.path, cached.url, verified)
+    } else {
+        if expected.is_none() {
+            multi.suspend(|| print_message(MessageType::Warning(&format!(
+                "No checksum is published for {} {}; it will not be verified.",
+                capitalize_first(&software.language),
+                software.version
+            ))));
+        }
+
+        let archive_path = api::archive::get_archive_path(software, expected.as_ref())?;
+        let pb = multi.add(ProgressBar::new(0));
+        pb.set_prefix(format!("{} {}", software.language, software.version));
+
+        let (source_url, actual) = request::download_from_mirrors(&urls, archive_path.to_str().unwrap(), expected.as_ref(), &pb).await?;
+        if source_url != software.url {
+            multi.suspend(|| print_message(MessageType::Info(&format!("Downloaded {} {} from mirror {}", software.language, software.version, auth::redact_url(&source_url)))));
+        }
+
+        // Failing to index the archive only costs a re-download later.
+        let _ = api::archive::record(&archive_path, &software.url, &actual, expected.is_some());
+        (archive_path, source_url, expected.map(|_| actual.to_string()))
+    };
+
+    let destination = install_dir.clone();
+    tokio::task::spawn_blocking(move || filesystem::extract_to(&archive_path, &destination))
+        .await
+        .map_err(io::Error::other)??;
+
+    Ok(Installed {
+        language: software.language.clone(),
+        version: software.version.clone(),
+        path: install_dir.to_str().unwrap().to_string(),
+        default: false,
+        checksum: verified,
+        url: Some(source_url),
+        platform: software.platform.clone(),
+        arch: software.arch.clone(),
+        // Only the architecture fallback installs a build for another architecture than asked for.
+        emulated: software.arch != api::get_arch(),
+    })
+}
+
+/// The version, followed by its target when it was installed for another machine.
+fn display_version(entry: &Installed) -> String {
+    if entry.emulated {
+        format!("{} ({}, emulated)", entry.version, entry.arch)
+    } else if entry.is_native() {
+        entry.version.clone()
+    } else {
+        format!("{} ({}-{})", entry.version, entry.platform, entry.arch)
+    }
+}
+
+/// Looks for an x64 build to run under emulation when no native build of `version` exists,
+/// following the `arch_fallback` policy from pkit.json.
+async fn find_emulated_build(language: &str, version: &str) -> Option<api::Version> {
+    let emulator = target::get_fallback_emulator()?;
+    let policy = Config::new().arch_fallback;
+    if policy == ArchFallback::Never {
+        return None;
     }
 
-    filesystem::extract(&archive_path).expect("Failed to extract archive");
-    filesystem::delete(&archive_path).expect("Failed to delete archive");
+    let software = get_language_version_safe(language, version, target::FALLBACK_ARCH).await.ok()?;
+    let name = format!("{} {}", capitalize_first(language), software.version);
 
-    println!();
-    print_box(&[("&aInstallation Complete&r", BoxAlignment::Center)], &BoxOptions::default());
-    println!();
-    println!("{}", colorize(&format!("  &e{} {}&r has been successfully installed.", capitalize_first(&software.language), software.version)));
-    println!();
+    if policy == ArchFallback::Ask {
+        println!();
+        println!("{}", colorize(&format!(
+            "  &eNo {} build of {} exists. Install the {} build to run under {}? (y/n): &r",
+            api::get_arch(), name, software.arch, emulator
+        )));
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input).unwrap();
+        if !input.trim().eq_ignore_ascii_case("y") {
+            return None;
+        }
+    } else {
+        print_message(MessageType::Info(&format!(
+            "No {} build of {} exists; using the {} build under {}",
+            api::get_arch(), name, software.arch, emulator
+        )));
+    }
 
-    println!("{}", colorize("  &eDo you want to set this version as the default? (y/n): &r"));
+    Some(software)
+}
+
+/// Asks once whether the freshly installed versions should become the defaults.
+fn ask_set_default(count: usize) -> bool {
+    let question = if count == 1 {
+        "  &eDo you want to set this version as the default? (y/n): &r"
+    } else {
+        "  &eDo you want to set these versions as the defaults? (y/n): &r"
+    };
+    println!("{}", colorize(question));
     let mut input = String::new();
     std::io::stdin().read_line(&mut input).unwrap();
+    input.trim().eq_ignore_ascii_case("y")
+}
 
-    let mut config = Config::new();
-    let default_path = pkit_dir
-        .join("bin")
-        .join(&software.language)
-        .join(&software.version);
+/// Installs every requested `language@version`.
+///
+/// All targets are resolved against the registries first, then downloaded in parallel and
+/// extracted concurrently. `default` answers the "set as default" question up front;
+/// when it is `None` the user is asked once at the end.
+pub async fn handle_install_command(packages: &[String], default: Option<bool>) {
+    let targets = match parse_install_targets(packages) {
+        Ok(targets) => targets,
+        Err(e) => {
+            print_usage_error_message(&e);
+            std::process::exit(1);
+        }
+    };
 
-    if input.trim().eq_ignore_ascii_case("y") {
-        config.add_install(&software.language, &software.version, default_path.to_str().unwrap(), true);
-        config.write_env_script().expect("Failed to write environment script");
-        
+    let config = Config::new();
+    let single = targets.len() == 1;
+    let mut resolved: Vec<api::Version> = Vec::new();
+    let mut failed: Vec<(String, String, ApiError)> = Vec::new();
+
+    for (requested_language, version) in &targets {
+        // Registries can publish aliases, so `nodejs` installs `node`.
+        let language = match api::resolve_language(requested_language).await {
+            Ok(language) => language,
+            Err(e) => {
+                if single && e.is_not_found() {
+                    let suggestions = api::suggest_languages(requested_language).await;
+                    print_language_not_found_message(requested_language, &suggestions);
+                } else if single {
+                    print_api_error_message(&e);
+                }
+                failed.push((requested_language.clone(), version.clone(), e));
+                continue;
+            }
+        };
+        let language = &language;
+
+        if let Some(installed) = config.get_target(language, version, api::get_platform(), api::get_arch()) {
+            print_already_installed_message(language, version, installed.default);
+            continue;
+        }
+
+        let software = match get_language_version_safe(language, version, api::get_arch()).await {
+            Ok(software) => Ok(software),
+            Err(e) if e.is_not_found() => find_emulated_build(language, version).await.ok_or(e),
+            Err(e) => Err(e),
+        };
+
+        match software {
+            Ok(software) => {
+                if software.version != *version {
+                    print_message(MessageType::Info(&format!(
+                        "Resolved {} {} to {}",
+                        capitalize_first(language),
+                        version,
+                        software.version
+                    )));
+                }
+                if let Some(installed) = config.get_target(language, &software.version, &software.platform, &software.arch) {
+                    print_already_installed_message(language, &software.version, installed.default);
+                    continue;
+                }
+                resolved.push(software);
+            }
+            Err(e) => {
+                if single && e.is_not_found() {
+                    let suggestions = api::suggest_versions(language, version).await;
+                    print_not_found_message(language, version, &e.to_string(), &suggestions);
+                } else if single {
+                    print_api_error_message(&e);
+                }
+                failed.push((language.clone(), version.clone(), e));
+            }
+        }
+    }
+
+    let mut installed: Vec<Installed> = Vec::new();
+
+    if !resolved.is_empty() {
         println!();
-        print_box(&[("&aSuccess&r", BoxAlignment::Center)], &BoxOptions::default());
+        let title = if resolved.len() == 1 {
+            format!("&aDownloading &e{} {}&r", capitalize_first(&resolved[0].language), resolved[0].version)
+        } else {
+            format!("&aDownloading &e{} packages&r", resolved.len())
+        };
+        print_box(&[(title.as_str(), BoxAlignment::Center)], &BoxOptions::default());
         println!();
-        println!("{}", colorize(&format!("  &e{} {}&r is now the default version.", capitalize_first(&software.language), software.version)));
+
+        let multi = MultiProgress::new();
+        let results = join_all(resolved.iter().map(|software| install_archive(software, &multi))).await;
+
+        for (software, result) in resolved.iter().zip(results) {
+            match result {
+                Ok(entry) => installed.push(entry),
+                Err(e) => failed.push((software.language.clone(), software.version.clone(), e)),
+            }
+        }
+    }
+
+    if !single {
+        print_install_summary(&installed, &failed);
+    } else if let Some(entry) = installed.first() {
         println!();
-        
-    } else {
-        config.add_install(&software.language, &software.version, default_path.to_str().unwrap(), false);
-        config.write_env_script().expect("Failed to write environment script");
+        print_box(&[("&aInstallation Complete&r", BoxAlignment::Center)], &BoxOptions::default());
         println!();
-        let usage_line = format!(" &3pkit default {} {}&r  &8-&r  Set this version as default later", software.language, software.version);
-        print_box(
-            &[(usage_line.as_str(), BoxAlignment::Left)],
-            &BoxOptions {
-                title: Some("Next Steps"),
-                ..Default::default()
-            },
-        );
+        println!("{}", colorize(&format!("  &e{} {}&r has been successfully installed.", capitalize_first(&entry.language), display_version(entry))));
+        println!();
+    } else if let Some((_, _, e)) = failed.first()
+        && !resolved.is_empty()
+    {
+        println!();
+        print_box(&[("&cInstallation Failed&r", BoxAlignment::Center)], &BoxOptions::default());
+        println!();
+        println!("{}", colorize(&format!("  &cError: {}&r", e)));
         println!();
     }
-}
 
-pub async fn handle_install_command(language: &str, version: &str) {
-    let config = Config::new();
-    
-    if let Some(installed) = config.get(language, version) {
-        print_already_installed_message(language, version, installed.default);
-        return;
-    }
+    let exit_code = failed.first().map(|(_, _, e)| e.exit_code()).unwrap_or(0);
 
-    let software = match get_language_version_safe(language, version).await {
-        Ok(software) => software,
-        Err(e) => {
-            print_not_found_message(language, version, &e);
-            return;
+    if !installed.is_empty() && target::is_cross() {
+        if default == Some(true) {
+            print_message(MessageType::Warning("Versions installed for another platform or architecture can't be set as default."));
         }
-    };
-    
-    install_software_with_data(software).await;
+
+        let mut config = Config::new();
+        for entry in installed {
+            config.add_install(entry);
+        }
+    } else if !installed.is_empty() {
+        // A language has one default, so when several of its versions were installed the
+        // newest one gets it, whatever order they were named in.
+        let mut defaults: Vec<&Installed> = Vec::new();
+        for entry in &installed {
+            match defaults.iter_mut().find(|chosen| chosen.language == entry.language) {
+                Some(chosen) if version::compare(&entry.version, &chosen.version).is_gt() => *chosen = entry,
+                Some(_) => {}
+                None => defaults.push(entry),
+            }
+        }
+        let is_chosen = |entry: &Installed| defaults.iter().any(|chosen| chosen.language == entry.language && chosen.version == entry.version);
+
+        let make_default = default.unwrap_or_else(|| ask_set_default(defaults.len()));
+
+        let mut config = Config::new();
+        for mut entry in installed.clone() {
+            entry.default = make_default && is_chosen(&entry);
+            config.add_install(entry);
+        }
+        config.write_env_script().expect("Failed to write environment script");
+
+        if make_default {
+            println!();
+            print_box(&[("&aSuccess&r", BoxAlignment::Center)], &BoxOptions::default());
+            println!();
+            for entry in &defaults {
+                println!("{}", colorize(&format!("  &e{} {}&r is now the default version.", capitalize_first(&entry.language), entry.version)));
+            }
+            for entry in installed.iter().filter(|entry| !is_chosen(entry)) {
+                println!("{}", colorize(&format!("  &8{} {} was installed but is not the default.&r", capitalize_first(&entry.language), entry.version)));
+            }
+            println!();
+        } else {
+            println!();
+            let usage_lines: Vec<String> = installed
+                .iter()
+                .map(|entry| format!(" &3pkit default {} {}&r  &8-&r  Set this version as default later", entry.language, entry.version))
+                .collect();
+            let lines: Vec<(&str, BoxAlignment)> = usage_lines.iter().map(|line| (line.as_str(), BoxAlignment::Left)).collect();
+            print_box(
+                &lines,
+                &BoxOptions {
+                    title: Some("Next Steps"),
+                    ..Default::default()
+                },
+            );
+            println!();
+        }
+    }
+
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
 }
 
-async fn get_language_version_safe(language: &str, version: &str) -> Result<api::Version, String> {
-    // Try to get the language first to check if it exists
-    let languages = api::get_languages().await;
-    if !languages.iter().any(|l| l.to_lowercase() == language.to_lowercase()) {
-        return Err(format!("Language '{}' not found", language));
+fn print_install_summary(installed: &[Installed], failed: &[(String, String, ApiError)]) {
+    println!();
+    print_box(&[("&aInstallation Summary&r", BoxAlignment::Center)], &BoxOptions::default());
+    println!();
+
+    let columns = [("Language", 14), ("Version", 14), ("Status", 50)];
+    print_table_header(&columns);
+
+    for entry in installed {
+        let language_str = format!("&e{}&r", capitalize_first(&entry.language));
+        let version_str = format!("&3{}&r", display_version(entry));
+        let values = [language_str.as_str(), version_str.as_str(), "&aInstalled&r"];
+        print_table_row(&columns, &values);
     }
-    
-    let language_info = api::get_language(language).await;
-    let version_exists = language_info.versions.iter()
-        .any(|v| v.version == version);
-    
-    if !version_exists {
-        return Err(format!("Version '{}' not found for language '{}'", version, language));
+
+    for (language, version, error) in failed {
+        let language_str = format!("&e{}&r", capitalize_first(language));
+        let version_str = format!("&3{}&r", version);
+        let status_str = format!("&cFailed: {}&r", error.title());
+        let values = [language_str.as_str(), version_str.as_str(), status_str.as_str()];
+        print_table_row(&columns, &values);
+    }
+
+    print_table_footer(&columns);
+    println!();
+
+    for (language, version, error) in failed {
+        println!("{}", colorize(&format!("  &e{} {}&r: &c{}&r", capitalize_first(language), version, error)));
     }
+    if !failed.is_empty() {
+        println!();
+    }
+}
+
+/// Resolves `request` (an exact version, a partial version, a range or a keyword such as
+/// `lts`) against the versions the registries offer for `arch`, then fetches that version.
+async fn get_language_version_safe(language: &str, request: &str, arch: &str) -> Result<api::Version, ApiError> {
+    let language_info = api::get_language_for_arch(language, arch).await?;
+    let candidates: Vec<Candidate> = language_info
+        .versions
+        .iter()
+        .map(Candidate::from_version)
+        .collect();
+
+    let Some(version) = resolver::resolve(request, &candidates) else {
+        if let Some(build) = language_info.incompatible.iter().find(|v| v.version == request)
+            && let Some(issue) = build.libc_issue()
+        {
+            return Err(ApiError::Incompatible(issue));
+        }
+        return Err(ApiError::NotFound(format!("Version '{}' not found for language '{}'", request, language)));
+    };
     
-    Ok(api::get_language_version(language, version).await)
+    api::get_language_version_for_arch(language, version, arch).await
 }
 
 fn print_already_installed_message(language: &str, version: &str, is_default: bool) {
@@ -133,12 +425,13 @@ fn print_already_installed_message(language: &str, version: &str, is_default: bo
     println!();
 }
 
-fn print_not_found_message(language: &str, version: &str, error: &str) {
+fn print_not_found_message(language: &str, version: &str, error: &str, suggestions: &[String]) {
     println!();
     print_box(&[("&cNot Found&r", BoxAlignment::Center)], &BoxOptions::default());
     println!();
     println!("{}", colorize(&format!("  &e{} version {}&r was not found.", capitalize_first(language), version)));
     println!("{}", colorize(&format!("  &8Error: {}&r", error)));
+    print_suggestions(suggestions);
     println!();
     let usage_line = format!(" &3pkit list {}&r  &8-&r  See available versions", language);
     print_box(
@@ -150,3 +443,55 @@ fn print_not_found_message(language: &str, version: &str, error: &str) {
     );
     println!();
 }
+
+fn print_language_not_found_message(language: &str, suggestions: &[String]) {
+    println!();
+    print_box(&[("&cNot Found&r", BoxAlignment::Center)], &BoxOptions::default());
+    println!();
+    println!("{}", colorize(&format!("  &e{}&r is not available in any registry.", capitalize_first(language))));
+    print_suggestions(suggestions);
+    println!();
+    print_box(
+        &[(" &3pkit list&r  &8-&r  View available languages", BoxAlignment::Left)],
+        &BoxOptions {
+            title: Some("Available Languages"),
+            ..Default::default()
+        },
+    );
+    println!();
+}
+
+fn print_suggestions(suggestions: &[String]) {
+    if !suggestions.is_empty() {
+        println!("{}", colorize(&format!("  &7Did you mean &a{}&7?&r", suggestions.join("&7, &a"))));
+    }
+}
+
+fn print_usage_error_message(error: &str) {
+    println!();
+    print_box(&[("&cInvalid Arguments&r", BoxAlignment::Center)], &BoxOptions::default());
+    println!();
+    println!("{}", colorize(&format!("  &c{}&r", error)));
+    println!();
+    let lines = [
+        (" &3pkit install <language> <version>&r          &8-&r  Install one version", BoxAlignment::Left),
+        (" &3pkit install <language>@<version> ...&r      &8-&r  Install several versions at once", BoxAlignment::Left),
+    ];
+    print_box(
+        &lines,
+        &BoxOptions {
+            title: Some("Usage"),
+            ..Default::default()
+        },
+    );
+    println!();
+}
+
+fn print_api_error_message(error: &ApiError) {
+    println!();
+    let title = format!("&c{}&r", error.title());
+    print_box(&[(title.as_str(), BoxAlignment::Center)], &BoxOptions::default());
+    println!();
+    println!("{}", colorize(&format!("  &cError: {}&r", error)));
+    println!();
+}
@@ -1,14 +1,21 @@
 use crate::{
-    api::{self},
-    filesystem::config::{Config, Installed},
+    api::{self, ApiError},
+    filesystem::config::{Alias, Config, Installed},
     formatter::{
         capitalize_first, colorize, print_box, print_table_footer, print_table_header,
         print_table_row, BoxAlignment, BoxOptions,
     },
+    version,
 };
 
 pub async fn print_available_languages() {
-    let mut languages: Vec<String> = api::get_languages().await;
+    let mut languages: Vec<String> = match api::get_languages().await {
+        Ok(languages) => languages,
+        Err(e) => {
+            print_api_error_message(&e);
+            std::process::exit(e.exit_code());
+        }
+    };
 
     for lang in languages.iter_mut() {
         *lang = capitalize_first(lang);
@@ -40,27 +47,69 @@ pub async fn print_available_languages() {
 }
 
 pub async fn print_available_language_versions(language: &str) {
-    let language_data: api::Language = api::get_language(language.to_lowercase().as_str()).await;
+    let language_data = match api::resolve_language(language).await {
+        Ok(name) => api::get_language(&name).await,
+        Err(e) => Err(e),
+    };
+    let mut language_data: api::Language = match language_data {
+        Ok(language_data) => language_data,
+        Err(e) if e.is_not_found() => {
+            let suggestions = api::suggest_languages(language).await;
+            print_language_not_found_message(language, &suggestions);
+            std::process::exit(e.exit_code());
+        }
+        Err(e) => {
+            print_api_error_message(&e);
+            std::process::exit(e.exit_code());
+        }
+    };
+
+    version::sort(&mut language_data.versions, |v| v.version.as_str());
 
     println!();
     let title = format!("&aAvailable Versions for &e{}&r", capitalize_first(&language_data.name));
     print_box(&[(title.as_str(), BoxAlignment::Center)], &BoxOptions::default());
     println!();
 
-    let columns = [("Version", 20), ("Platform", 16), ("Architecture", 14)];
+    let columns = [("Version", 12), ("Platform", 8), ("Arch", 6), ("Released", 10), ("EOL", 10), ("Support", 14)];
 
     print_table_header(&columns);
 
     for version in language_data.versions.iter() {
-        let version_str = format!("&e{}&r", version.version);
+        let version_color = if version.is_eol() { "c" } else if version.lts { "a" } else { "e" };
+        let version_str = format!("&{}{}&r", version_color, version.version);
         let platform_str = format!("&3{}&r", version.platform);
         let arch_str = format!("&5{}&r", version.arch);
+        let released_str = format!("&8{}&r", version.release_date.as_deref().unwrap_or("-"));
+        let eol_str = match version.eol.as_deref() {
+            Some(eol) if version.is_eol() => format!("&c{}&r", if eol.is_empty() { "yes" } else { eol }),
+            Some(eol) => format!("&8{}&r", eol),
+            None => "&8-&r".to_string(),
+        };
+        let support_str = format_support(version);
 
-        let values = [version_str.as_str(), platform_str.as_str(), arch_str.as_str()];
+        let values = [
+            version_str.as_str(),
+            platform_str.as_str(),
+            arch_str.as_str(),
+            released_str.as_str(),
+            eol_str.as_str(),
+            support_str.as_str(),
+        ];
         print_table_row(&columns, &values);
     }
 
     print_table_footer(&columns);
+
+    if !language_data.incompatible.is_empty() {
+        println!();
+        println!("{}", colorize(&format!(
+            "  &8{} build(s) were skipped because they are not compatible with this system's C library ({}).&r",
+            language_data.incompatible.len(),
+            api::target::get_libc().map(|libc| libc.to_string()).unwrap_or_default()
+        )));
+    }
+
     println!();
     let usage_line = format!(" &3pkit install {} <version>&r  &8-&r  Install a specific version", language_data.name);
     print_box(
@@ -73,9 +122,35 @@ pub async fn print_available_language_versions(language: &str) {
     println!();
 }
 
+/// Summarises the support status of a version: LTS in green, end of life in red.
+fn format_support(version: &api::Version) -> String {
+    let mut labels: Vec<String> = Vec::new();
+
+    if version.is_eol() {
+        labels.push("&cEOL&r".to_string());
+    } else if version.lts {
+        match &version.codename {
+            Some(codename) => labels.push(format!("&aLTS ({})&r", codename)),
+            None => labels.push("&aLTS&r".to_string()),
+        }
+    }
+    if let Some(channel) = version.channel.as_deref().filter(|channel| *channel != "stable") {
+        labels.push(format!("&6{}&r", channel));
+    }
+    if version.deprecated {
+        labels.push("&6deprecated&r".to_string());
+    }
+
+    if labels.is_empty() {
+        return "&8-&r".to_string();
+    }
+    labels.join(" ")
+}
+
 pub fn print_installed_languages() {
     let config = Config::new();
-    let installed: Vec<Installed> = config.installed;
+    let mut installed: Vec<Installed> = config.installed.clone();
+    installed.sort_by(|a, b| a.language.cmp(&b.language).then_with(|| version::compare(&a.version, &b.version)));
 
     println!();
     print_box(&[("&aInstalled Languages&r", BoxAlignment::Center)], &BoxOptions::default());
@@ -96,20 +171,28 @@ pub fn print_installed_languages() {
             },
         );
     } else {
-        let columns = [("Language", 20), ("Version", 17), ("Status", 12)];
+        let columns = [("Language", 14), ("Version", 14), ("Target", 20), ("Status", 12)];
 
         print_table_header(&columns);
 
         for lang in installed.iter() {
             let language_str = format!("&e{}&r", lang.language);
             let version_str = format!("&3{}&r", lang.version);
+            let target_str = if lang.emulated {
+                format!("&6{}-{} emulated&r", lang.platform, lang.arch)
+            } else if lang.is_native() {
+                format!("&8{}-{}&r", lang.platform, lang.arch)
+            } else {
+                format!("&5{}-{}&r", lang.platform, lang.arch)
+            };
             let status_str = if lang.default { "&aDefault&r".to_string() } else { "&8Available&r".to_string() };
 
-            let values = [language_str.as_str(), version_str.as_str(), status_str.as_str()];
+            let values = [language_str.as_str(), version_str.as_str(), target_str.as_str(), status_str.as_str()];
             print_table_row(&columns, &values);
         }
 
         print_table_footer(&columns);
+        print_aliases(&config);
         println!();
         let lines = [
             (" &3pkit default <language>&r  &8-&r  Set a language as default                      ", BoxAlignment::Left),
@@ -126,6 +209,59 @@ pub fn print_installed_languages() {
     println!();
 }
 
+fn print_aliases(config: &Config) {
+    if config.aliases.is_empty() {
+        return;
+    }
+
+    let mut aliases: Vec<&Alias> = config.aliases.iter().collect();
+    aliases.sort_by(|a, b| a.language.cmp(&b.language).then_with(|| a.name.cmp(&b.name)));
+
+    println!();
+    let columns = [("Language", 14), ("Alias", 14), ("Version", 20), ("Status", 12)];
+    print_table_header(&columns);
+
+    for alias in aliases {
+        let language_str = format!("&e{}&r", alias.language);
+        let alias_str = format!("&a{}&r", alias.name);
+        let version_str = format!("&3{}&r", alias.version);
+        let status_str = if config.get(&alias.language, &alias.version).is_some() { "&8Installed&r" } else { "&cMissing&r" };
+
+        let values = [language_str.as_str(), alias_str.as_str(), version_str.as_str(), status_str];
+        print_table_row(&columns, &values);
+    }
+
+    print_table_footer(&columns);
+}
+
+fn print_language_not_found_message(language: &str, suggestions: &[String]) {
+    println!();
+    print_box(&[("&cNot Found&r", BoxAlignment::Center)], &BoxOptions::default());
+    println!();
+    println!("{}", colorize(&format!("  &e{}&r is not available in any registry.", capitalize_first(language))));
+    if !suggestions.is_empty() {
+        println!("{}", colorize(&format!("  &7Did you mean &a{}&7?&r", suggestions.join("&7, &a"))));
+    }
+    println!();
+    print_box(
+        &[(" &3pkit list&r  &8-&r  View available languages", BoxAlignment::Left)],
+        &BoxOptions {
+            title: Some("Available Languages"),
+            ..Default::default()
+        },
+    );
+    println!();
+}
+
+fn print_api_error_message(error: &ApiError) {
+    println!();
+    let title = format!("&c{}&r", error.title());
+    print_box(&[(title.as_str(), BoxAlignment::Center)], &BoxOptions::default());
+    println!();
+    println!("{}", colorize(&format!("  &cError: {}&r", error)));
+    println!();
+}
+
 pub async fn handle_list_command(language: Option<&String>, installed: bool) {
     if installed {
         print_installed_languages();
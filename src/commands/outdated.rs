@@ -0,0 +1,157 @@
+use crate::{
+    api::{self, ApiError},
+    filesystem::config::{Config, Installed},
+    formatter::{
+        capitalize_first, colorize, print_box, print_table_footer, print_table_header, print_table_row, BoxAlignment,
+        BoxOptions,
+    },
+    resolver::{self, Candidate},
+    version,
+};
+
+/// Newer releases found in the registry for one installed version.
+struct Report<'a> {
+    installed: &'a Installed,
+    /// Newest release with the same major and minor version.
+    latest_patch: Option<String>,
+    latest: Option<String>,
+    /// `None` when the registry no longer lists the installed version.
+    eol: Option<bool>,
+}
+
+impl Report<'_> {
+    fn is_outdated(&self) -> bool {
+        let newer = |candidate: &Option<String>| {
+            candidate.as_deref().is_some_and(|v| version::compare(v, &self.installed.version).is_gt())
+        };
+        newer(&self.latest_patch) || newer(&self.latest)
+    }
+}
+
+fn build_report<'a>(installed: &'a Installed, language_data: &api::Language) -> Report<'a> {
+    let candidates: Vec<Candidate> = language_data.versions.iter().map(Candidate::from_version).collect();
+
+    Report {
+        installed,
+        latest_patch: resolver::newest_in_line(&installed.version, 2, &candidates).map(String::from),
+        latest: resolver::resolve("latest", &candidates).map(String::from),
+        eol: language_data
+            .versions
+            .iter()
+            .find(|v| version::same(&v.version, &installed.version))
+            .map(|v| v.is_eol()),
+    }
+}
+
+pub async fn handle_outdated_command(language: Option<&str>) {
+    let config = Config::new();
+
+    // Cross-installs are left out: the registry is queried for this machine's builds.
+    let mut installed: Vec<&Installed> = config
+        .installed
+        .iter()
+        .filter(|install| install.is_native() && language.is_none_or(|language| install.language == language))
+        .collect();
+    installed.sort_by(|a, b| a.language.cmp(&b.language).then_with(|| version::compare(&a.version, &b.version)));
+
+    if installed.is_empty() {
+        print_nothing_installed_message(language);
+        return;
+    }
+
+    let mut languages: Vec<&str> = installed.iter().map(|install| install.language.as_str()).collect();
+    languages.dedup();
+
+    let mut reports: Vec<Report> = Vec::new();
+    let mut failure: Option<ApiError> = None;
+    for language in languages {
+        match api::get_language(language).await {
+            Ok(language_data) => reports.extend(
+                installed
+                    .iter()
+                    .filter(|install| install.language == language)
+                    .map(|install| build_report(install, &language_data)),
+            ),
+            Err(e) => {
+                print_api_error_message(language, &e);
+                failure = Some(e);
+            }
+        }
+    }
+
+    if !reports.is_empty() {
+        print_report_table(&reports);
+    }
+
+    if let Some(e) = failure {
+        std::process::exit(e.exit_code());
+    }
+}
+
+fn print_report_table(reports: &[Report]) {
+    println!();
+    print_box(&[("&aOutdated Languages&r", BoxAlignment::Center)], &BoxOptions::default());
+    println!();
+
+    let columns = [("Language", 12), ("Current", 14), ("Latest Patch", 14), ("Latest", 14), ("Support", 10)];
+    print_table_header(&columns);
+
+    for report in reports {
+        let current = &report.installed.version;
+        let newer = |candidate: &Option<String>| match candidate {
+            Some(v) if version::compare(v, current).is_gt() => format!("&a{}&r", v),
+            _ => "&8-&r".to_string(),
+        };
+
+        let language_str = format!("&e{}&r", report.installed.language);
+        let current_str = if report.is_outdated() { format!("&6{}&r", current) } else { format!("&3{}&r", current) };
+        let patch_str = newer(&report.latest_patch);
+        let latest_str = newer(&report.latest);
+        let support_str = match report.eol {
+            Some(true) => "&cEOL&r",
+            Some(false) => "&aSupported&r",
+            None => "&8Unknown&r",
+        };
+
+        let values = [language_str.as_str(), current_str.as_str(), patch_str.as_str(), latest_str.as_str(), support_str];
+        print_table_row(&columns, &values);
+    }
+
+    print_table_footer(&columns);
+    println!();
+
+    if reports.iter().any(|report| report.is_outdated()) {
+        print_box(
+            &[
+                (" &3pkit upgrade [language]&r          &8-&r  Newest release of each major version", BoxAlignment::Left),
+                (" &3pkit upgrade [language] --major&r  &8-&r  Newest release overall", BoxAlignment::Left),
+            ],
+            &BoxOptions {
+                title: Some("Update"),
+                ..Default::default()
+            },
+        );
+    } else {
+        println!("{}", colorize("  &aEverything is up to date.&r"));
+    }
+    println!();
+}
+
+pub fn print_nothing_installed_message(language: Option<&str>) {
+    println!();
+    let title = match language {
+        Some(language) => format!("&eNo versions of {} are installed.&r", capitalize_first(language)),
+        None => "&eNo languages are currently installed.&r".to_string(),
+    };
+    print_box(&[(title.as_str(), BoxAlignment::Center)], &BoxOptions::default());
+    println!();
+}
+
+pub fn print_api_error_message(language: &str, error: &ApiError) {
+    println!();
+    let title = format!("&c{}&r", error.title());
+    print_box(&[(title.as_str(), BoxAlignment::Center)], &BoxOptions::default());
+    println!();
+    println!("{}", colorize(&format!("  &cCould not check {}: {}&r", capitalize_first(language), error)));
+    println!();
+}
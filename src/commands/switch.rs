@@ -1,7 +1,8 @@
 use crate::filesystem::config::Config;
 use crate::formatter::{
-    capitalize_first, print_box, BoxAlignment, BoxOptions,
+    capitalize_first, print_box, print_message, BoxAlignment, BoxOptions, MessageType,
 };
+use crate::resolver;
 use std::fs;
 use std::io::Write;
 use std::path::PathBuf;
@@ -11,14 +12,26 @@ pub fn handle_switch_command(language: &str, version: &str) {
     let config = Config::new();
 
     // Check if the specified language and version is installed
-    if let Some(installed) = config.get(language, version) {
+    let installed = match resolver::resolve_installed(&config, language, version) {
+        Ok(installed) => installed,
+        Err(e) => {
+            print_error_message(&e);
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(installed) = installed {
+        if installed.version != version {
+            print_message(MessageType::Info(&format!("Resolved {} {} to {}", capitalize_first(language), version, installed.version)));
+        }
+
         // Create session-specific environment script
-        if let Err(e) = write_session_env_script(&config, language, &installed.path) {
+        if let Err(e) = write_session_env_script(&config, language, &installed.version, &installed.path) {
             print_error_message(&format!("Failed to create session environment: {}", e));
             std::process::exit(1);
         }
         
-        print_success_message(language, version);
+        print_success_message(language, &installed.version);
 
     } else {
         print_not_installed_message(language, version);
@@ -26,7 +39,7 @@ pub fn handle_switch_command(language: &str, version: &str) {
     }
 }
 
-fn write_session_env_script(config: &Config, session_language: &str, session_path: &str) -> std::io::Result<()> {
+fn write_session_env_script(config: &Config, session_language: &str, session_version: &str, session_path: &str) -> std::io::Result<()> {
     let pkit_dir = get_pkit_dir()?;
     
     let env_script_filename = if cfg!(windows) {
@@ -55,6 +68,14 @@ fn write_session_env_script(config: &Config, session_language: &str, session_pat
     } else {
         writeln!(file, "export PATH=\"{}:$PATH\"", session_bin_path.display())?;
     }
+
+    // Lets `pkit current` and `pkit env` see the switch; project files still take precedence.
+    let session_variable = resolver::session_variable(session_language);
+    if cfg!(windows) {
+        writeln!(file, "$env:{} = \"{}\"", session_variable, session_version)?;
+    } else {
+        writeln!(file, "export {}=\"{}\"", session_variable, session_version)?;
+    }
     
     // Add other default languages (excluding the session language to avoid duplication)
     for install in &config.installed {
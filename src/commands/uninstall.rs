@@ -1,8 +1,10 @@
 use crate::{
     filesystem::{config::Config, get_pkit_dir},
-    formatter::{capitalize_first, colorize, print_box, BoxAlignment, BoxOptions},
+    formatter::{capitalize_first, colorize, print_box, print_message, BoxAlignment, BoxOptions, MessageType},
+    resolver, version,
 };
 use std::fs;
+use std::path::PathBuf;
 
 pub fn handle_uninstall_command(language: &str, version: Option<&String>, all: bool) {
     let mut config = Config::new();
@@ -16,24 +18,38 @@ pub fn handle_uninstall_command(language: &str, version: Option<&String>, all: b
     }
 }
 
-fn uninstall_specific_version(language: &str, version: &str, config: &mut Config) {
-    if let Some(installed) = config.get(language, version) {
+fn uninstall_specific_version(language: &str, request: &str, config: &mut Config) {
+    let resolved = match resolver::resolve_installed(config, language, request) {
+        Ok(installed) => installed.cloned(),
+        Err(e) => {
+            print_message(MessageType::Error(&e));
+            std::process::exit(1);
+        }
+    };
+
+    // Prefer the native install; fall back to one made for another platform.
+    let installed = resolved.or_else(|| {
+        config.installed.iter().find(|pkg| pkg.language == language && pkg.version == request).cloned()
+    });
+
+    if let Some(installed) = installed {
+        let version = installed.version.as_str();
+        if version != request && !confirm_resolved(config, language, request, version) {
+            println!("{}", colorize("  &eUninstall cancelled.&r"));
+            return;
+        }
         let was_default = installed.default;
         
         // Remove from config
-        config.remove_install(language, version);
+        config.remove_target(language, version, &installed.platform, &installed.arch);
         
         // Remove the actual installation directory
-        let pkit_dir = get_pkit_dir().expect("Failed to get pkit directory");
-        let version_dir = pkit_dir
-            .join("bin")
-            .join(language)
-            .join(version);
+        let version_dir = PathBuf::from(&installed.path);
             
-        if version_dir.exists() {
-            if let Err(e) = fs::remove_dir_all(&version_dir) {
-                eprintln!("Warning: Failed to remove directory {:?}: {}", version_dir, e);
-            }
+        if version_dir.exists()
+            && let Err(e) = fs::remove_dir_all(&version_dir)
+        {
+            eprintln!("Warning: Failed to remove directory {:?}: {}", version_dir, e);
         }
         
         // If this was the default version, check if there are other versions and prompt to set a new default
@@ -45,9 +61,32 @@ fn uninstall_specific_version(language: &str, version: &str, config: &mut Config
         config.write_env_script().expect("Failed to write environment script");
         
         print_success_message(language, version);
+        config.warn_dangling_aliases(language);
     } else {
-        print_not_installed_message(language, version);
+        print_not_installed_message(language, request);
+    }
+}
+
+/// Asks before removing `version` when it was picked for a partial version, range, keyword
+/// or alias rather than named exactly, listing the other installed versions that also fit.
+fn confirm_resolved(config: &Config, language: &str, request: &str, version: &str) -> bool {
+    let parsed = resolver::VersionRequest::parse(request);
+    let mut matches: Vec<&str> = config
+        .installed
+        .iter()
+        .filter(|pkg| pkg.language == language && pkg.is_native() && parsed.matches(&pkg.version))
+        .map(|pkg| pkg.version.as_str())
+        .collect();
+    version::sort(&mut matches, |v| v);
+
+    println!();
+    if matches.len() > 1 {
+        println!("{}", colorize(&format!("  &8{} {} matches {}.&r", capitalize_first(language), request, matches.join(", "))));
     }
+    println!("{}", colorize(&format!("  &e{} {} resolves to {}. Uninstall it? (y/n): &r", capitalize_first(language), request, version)));
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input).unwrap();
+    input.trim().eq_ignore_ascii_case("y")
 }
 
 fn uninstall_all_versions(language: &str, config: &mut Config) {
@@ -81,16 +120,17 @@ fn uninstall_all_versions(language: &str, config: &mut Config) {
     let pkit_dir = get_pkit_dir().expect("Failed to get pkit directory");
     let language_dir = pkit_dir.join("bin").join(language);
     
-    if language_dir.exists() {
-        if let Err(e) = fs::remove_dir_all(&language_dir) {
-            eprintln!("Warning: Failed to remove directory {:?}: {}", language_dir, e);
-        }
+    if language_dir.exists()
+        && let Err(e) = fs::remove_dir_all(&language_dir)
+    {
+        eprintln!("Warning: Failed to remove directory {:?}: {}", language_dir, e);
     }
     
     // Update environment script
     config.write_env_script().expect("Failed to write environment script");
     
     print_all_versions_removed_message(language, &installed_versions);
+    config.warn_dangling_aliases(language);
 }
 
 fn handle_default_removal(language: &str, config: &mut Config) {
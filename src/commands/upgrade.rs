@@ -0,0 +1,208 @@
+use crate::{
+    api::{self, ApiError},
+    commands::{install, outdated},
+    filesystem::config::{Config, Installed},
+    formatter::{
+        capitalize_first, colorize, print_box, print_table_footer, print_table_header, print_table_row,
+        BoxAlignment, BoxOptions,
+    },
+    resolver::{self, Candidate},
+    version,
+};
+use futures_util::future::join_all;
+use indicatif::MultiProgress;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// One installed version and the newer release that replaces it.
+struct Upgrade {
+    from: Installed,
+    to: api::Version,
+}
+
+/// Picks the upgrades for the installed versions of one language.
+///
+/// Versions are grouped into lines by major version (or all together with `major`), and the
+/// newest installed version of each line moves to the newest release of that line.
+fn plan_upgrades(installed: &[&Installed], language_data: &api::Language, major: bool) -> Vec<Upgrade> {
+    let candidates: Vec<Candidate> = language_data.versions.iter().map(Candidate::from_version).collect();
+
+    let mut lines: BTreeMap<Option<u64>, &Installed> = BTreeMap::new();
+    for install in installed {
+        let line = if major { None } else { version::release(&install.version).first().copied() };
+        let newest = lines.entry(line).or_insert(install);
+        if version::compare(&install.version, &newest.version).is_gt() {
+            *newest = install;
+        }
+    }
+
+    lines
+        .into_values()
+        .filter_map(|current| {
+            let target = if major {
+                resolver::resolve("latest", &candidates)
+            } else {
+                resolver::newest_in_line(&current.version, 1, &candidates)
+            }?;
+            if !version::compare(target, &current.version).is_gt() {
+                return None;
+            }
+            let to = language_data.versions.iter().find(|v| v.version == target)?.clone();
+            Some(Upgrade { from: current.clone(), to })
+        })
+        .collect()
+}
+
+/// Whether the superseded versions should go; `remove` answers up front, otherwise ask.
+fn ask_remove_superseded(remove: Option<bool>, count: usize) -> bool {
+    if let Some(remove) = remove {
+        return remove;
+    }
+    let question = if count == 1 {
+        "  &eDo you want to remove the version that was upgraded from? (y/n): &r"
+    } else {
+        "  &eDo you want to remove the versions that were upgraded from? (y/n): &r"
+    };
+    println!("{}", colorize(question));
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input).unwrap();
+    input.trim().eq_ignore_ascii_case("y")
+}
+
+/// Moves every installed line of `language` (or of every language) to its newest release.
+///
+/// The new version takes over the default flag of the one it replaces. `remove` decides
+/// whether the replaced versions are uninstalled; when it is `None` the user is asked.
+pub async fn handle_upgrade_command(language: Option<&str>, major: bool, remove: Option<bool>) {
+    let config = Config::new();
+
+    // Only builds for this machine are upgraded; emulated and cross-installs are left alone.
+    let mut installed: Vec<&Installed> = config
+        .installed
+        .iter()
+        .filter(|install| !install.emulated && install.is_native() && language.is_none_or(|language| install.language == language))
+        .collect();
+    installed.sort_by(|a, b| a.language.cmp(&b.language));
+
+    if installed.is_empty() {
+        outdated::print_nothing_installed_message(language);
+        return;
+    }
+
+    let mut languages: Vec<&str> = installed.iter().map(|install| install.language.as_str()).collect();
+    languages.dedup();
+
+    let mut upgrades: Vec<Upgrade> = Vec::new();
+    let mut lookup_failure: Option<ApiError> = None;
+    for language in languages {
+        let language_installed: Vec<&Installed> = installed.iter().copied().filter(|install| install.language == language).collect();
+        match api::get_language(language).await {
+            Ok(language_data) => upgrades.extend(plan_upgrades(&language_installed, &language_data, major)),
+            Err(e) => {
+                outdated::print_api_error_message(language, &e);
+                lookup_failure = Some(e);
+            }
+        }
+    }
+
+    if upgrades.is_empty() {
+        if let Some(e) = lookup_failure {
+            std::process::exit(e.exit_code());
+        }
+        println!();
+        print_box(&[("&aEverything is up to date&r", BoxAlignment::Center)], &BoxOptions::default());
+        println!();
+        return;
+    }
+
+    println!();
+    let title = if upgrades.len() == 1 {
+        format!("&aUpgrading &e{} {}&a to &e{}&r", capitalize_first(&upgrades[0].from.language), upgrades[0].from.version, upgrades[0].to.version)
+    } else {
+        format!("&aUpgrading &e{} versions&r", upgrades.len())
+    };
+    print_box(&[(title.as_str(), BoxAlignment::Center)], &BoxOptions::default());
+    println!();
+
+    let multi = MultiProgress::new();
+    let results = join_all(upgrades.iter().map(|upgrade| install::install_archive(&upgrade.to, &multi))).await;
+
+    let mut config = Config::new();
+    let mut upgraded: Vec<&Upgrade> = Vec::new();
+    let mut failed: Vec<(&Upgrade, ApiError)> = Vec::new();
+    for (upgrade, result) in upgrades.iter().zip(results) {
+        match result {
+            Ok(mut entry) => {
+                entry.default = upgrade.from.default;
+                config.add_install(entry);
+                upgraded.push(upgrade);
+            }
+            Err(e) => failed.push((upgrade, e)),
+        }
+    }
+
+    if !upgraded.is_empty() && ask_remove_superseded(remove, upgraded.len()) {
+        for upgrade in &upgraded {
+            let from = &upgrade.from;
+            config.remove_target(&from.language, &from.version, &from.platform, &from.arch);
+
+            let version_dir = PathBuf::from(&from.path);
+            if version_dir.exists()
+                && let Err(e) = fs::remove_dir_all(&version_dir)
+            {
+                eprintln!("Warning: Failed to remove directory {:?}: {}", version_dir, e);
+            }
+        }
+        let mut languages: Vec<&str> = upgraded.iter().map(|upgrade| upgrade.from.language.as_str()).collect();
+        languages.dedup();
+        for language in languages {
+            config.warn_dangling_aliases(language);
+        }
+    }
+
+    config.write_env_script().expect("Failed to write environment script");
+
+    print_upgrade_summary(&upgraded, &failed);
+
+    if let Some(e) = failed.first().map(|(_, e)| e).or(lookup_failure.as_ref()) {
+        std::process::exit(e.exit_code());
+    }
+}
+
+fn print_upgrade_summary(upgraded: &[&Upgrade], failed: &[(&Upgrade, ApiError)]) {
+    println!();
+    print_box(&[("&aUpgrade Summary&r", BoxAlignment::Center)], &BoxOptions::default());
+    println!();
+
+    let columns = [("Language", 14), ("From", 14), ("To", 14), ("Status", 24)];
+    print_table_header(&columns);
+
+    for upgrade in upgraded {
+        let language_str = format!("&e{}&r", capitalize_first(&upgrade.from.language));
+        let from_str = format!("&8{}&r", upgrade.from.version);
+        let to_str = format!("&3{}&r", upgrade.to.version);
+        let status_str = if upgrade.from.default { "&aUpgraded, default&r" } else { "&aUpgraded&r" };
+        let values = [language_str.as_str(), from_str.as_str(), to_str.as_str(), status_str];
+        print_table_row(&columns, &values);
+    }
+
+    for (upgrade, error) in failed {
+        let language_str = format!("&e{}&r", capitalize_first(&upgrade.from.language));
+        let from_str = format!("&8{}&r", upgrade.from.version);
+        let to_str = format!("&3{}&r", upgrade.to.version);
+        let status_str = format!("&cFailed: {}&r", error.title());
+        let values = [language_str.as_str(), from_str.as_str(), to_str.as_str(), status_str.as_str()];
+        print_table_row(&columns, &values);
+    }
+
+    print_table_footer(&columns);
+    println!();
+
+    for (upgrade, error) in failed {
+        println!("{}", colorize(&format!("  &e{} {}&r: &c{}&r", capitalize_first(&upgrade.to.language), upgrade.to.version, error)));
+    }
+    if !failed.is_empty() {
+        println!();
+    }
+}
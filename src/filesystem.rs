@@ -27,15 +27,49 @@ pub fn write(path: &Path, contents: &str) -> io::Result<()> {
     fs::write(path, contents)
 }
 
+/// Writes `contents` to a temporary file next to `path` and renames it into place, so
+/// readers see either the old file or the new one and never a half-written one.
+pub fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    let file_name = path.file_name().and_then(|name| name.to_str()).unwrap_or("file");
+    let temp_path = path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()));
+    write(&temp_path, contents)?;
+    fs::rename(&temp_path, path).inspect_err(|_| {
+        let _ = fs::remove_file(&temp_path);
+    })
+}
+
 pub fn delete(path: &Path) -> io::Result<()> {
     fs::remove_file(path)
 }
 
+/// Total size in bytes of every file below `path` (0 if it does not exist).
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                dir_size(&path)
+            } else {
+                entry.metadata().map(|m| m.len()).unwrap_or(0)
+            }
+        })
+        .sum()
+}
+
 pub fn extract(archive_path: &Path) -> io::Result<()> {
     let destination = archive_path.parent().ok_or_else(|| {
         io::Error::new(io::ErrorKind::InvalidInput, "Invalid archive path")
     })?;
+    extract_to(archive_path, destination)
+}
 
+/// Extracts `archive_path` into `destination`, leaving the archive where it is.
+pub fn extract_to(archive_path: &Path, destination: &Path) -> io::Result<()> {
     let extension = archive_path
         .extension()
         .and_then(|s| s.to_str())
@@ -75,10 +109,10 @@ fn unzip_file(zip_path: &Path, destination: &Path) -> io::Result<()> {
         if file.name().ends_with('/') {
             fs::create_dir_all(&outpath)?;
         } else {
-            if let Some(p) = outpath.parent() {
-                if !p.exists() {
-                    fs::create_dir_all(p)?;
-                }
+            if let Some(p) = outpath.parent()
+                && !p.exists()
+            {
+                fs::create_dir_all(p)?;
             }
             let mut outfile = File::create(&outpath)?;
             io::copy(&mut file, &mut outfile)?;
@@ -145,10 +179,10 @@ fn find_dir_with_bin(dir: &Path) -> io::Result<Option<PathBuf>> {
         let entry = entry?;
         let path = entry.path();
 
-        if path.is_dir() {
-            if let Some(found_dir) = find_dir_with_bin(&path)? {
-                return Ok(Some(found_dir));
-            }
+        if path.is_dir()
+            && let Some(found_dir) = find_dir_with_bin(&path)?
+        {
+            return Ok(Some(found_dir));
         }
     }
 
@@ -3,7 +3,9 @@ use std::path::{Path, PathBuf};
 use std::io::{self, Write};
 use crate::filesystem::{self, get_pkit_dir};
 use json;
-use crate::formatter::{capitalize_first, print_box, BoxAlignment, BoxOptions};
+use crate::api::cache::DEFAULT_CACHE_TTL;
+use crate::api::target;
+use crate::formatter::{capitalize_first, print_box, print_message, BoxAlignment, BoxOptions, MessageType};
 
 
 #[derive(Clone)]
@@ -12,6 +14,23 @@ pub struct Installed {
     pub version: String,
     pub path: String,
     pub default: bool,
+    pub checksum: Option<String>,
+    pub url: Option<String>,
+    pub platform: String,
+    pub arch: String,
+    /// Built for another architecture and run under emulation (Rosetta, binfmt, ...).
+    pub emulated: bool,
+}
+
+impl Installed {
+    /// Whether this install runs on the machine pkit runs on, natively or under emulation.
+    pub fn is_native(&self) -> bool {
+        self.emulated || target::is_host(&self.platform, &self.arch)
+    }
+
+    fn is_target(&self, language: &str, version: &str, platform: &str, arch: &str) -> bool {
+        self.language == language && self.version == version && self.platform == platform && self.arch == arch
+    }
 }
 
 pub struct Source {
@@ -19,10 +38,192 @@ pub struct Source {
     pub path: String,
 }
 
+/// A user-chosen name for an installed version, such as `work` for node 20.11.0.
+#[derive(Clone)]
+pub struct Alias {
+    pub language: String,
+    pub name: String,
+    pub version: String,
+}
+
+/// Alternate locations for artifacts whose URL starts with `prefix`.
+#[derive(Clone)]
+pub struct Mirror {
+    pub prefix: String,
+    pub urls: Vec<String>,
+}
+
+/// Credentials for a private registry, from the `auth` object of a registry entry.
+#[derive(Clone, Default)]
+pub struct RegistryAuth {
+    /// Bearer token stored in pkit.json.
+    pub token: Option<String>,
+    /// Environment variable holding a bearer token; wins over `token` when set.
+    pub token_env: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl RegistryAuth {
+    fn from_json(json_data: &json::JsonValue) -> RegistryAuth {
+        let field = |name: &str| json_data[name].as_str().map(String::from);
+        RegistryAuth {
+            token: field("token"),
+            token_env: field("token_env"),
+            username: field("username"),
+            password: field("password"),
+        }
+    }
+
+    fn to_json(&self) -> json::JsonValue {
+        let mut json_data = json::JsonValue::new_object();
+        let fields = [("token", &self.token), ("token_env", &self.token_env), ("username", &self.username), ("password", &self.password)];
+        for (name, value) in fields {
+            if let Some(value) = value {
+                json_data[name] = value.clone().into();
+            }
+        }
+        json_data
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.token.is_none() && self.token_env.is_none() && self.username.is_none()
+    }
+}
+
+#[derive(Clone)]
+pub struct Registry {
+    pub name: String,
+    pub url: String,
+    pub public_keys: Vec<String>,
+    /// Serve the registry from plain JSON files instead of the HTTP API.
+    pub static_index: bool,
+    pub auth: RegistryAuth,
+}
+
+impl Registry {
+    /// Builds a registry entry named after the host of `url`.
+    pub fn from_url(url: &str) -> Registry {
+        let url = url.trim_end_matches('/');
+        let name = url
+            .split("://")
+            .last()
+            .and_then(|rest| rest.split('/').next())
+            .filter(|host| !host.is_empty())
+            .unwrap_or(url);
+
+        Registry {
+            name: name.to_string(),
+            url: url.to_string(),
+            public_keys: Vec::new(),
+            static_index: false,
+            auth: RegistryAuth::default(),
+        }
+    }
+
+    /// Static registries are read as files: `file://` URLs and plain paths always are,
+    /// HTTP ones when `"static": true` is set.
+    pub fn is_static(&self) -> bool {
+        self.static_index || !(self.url.starts_with("http://") || self.url.starts_with("https://"))
+    }
+}
+
+/// HTTP settings from the `network` section of pkit.json.
+#[derive(Clone)]
+pub struct NetworkSettings {
+    /// Seconds to wait for a connection to be established.
+    pub connect_timeout: u64,
+    /// Seconds to wait for the next chunk of a response.
+    pub read_timeout: u64,
+    /// How many times a transient failure is retried.
+    pub retries: u32,
+    /// HTTP(S) or SOCKS proxy for all traffic; when unset the standard proxy variables are used.
+    pub proxy: Option<String>,
+    /// Comma-separated hosts that bypass `proxy` (defaults to `NO_PROXY`).
+    pub no_proxy: Option<String>,
+    /// PEM files with extra root certificates to trust.
+    pub ca_certificates: Vec<String>,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        NetworkSettings {
+            connect_timeout: 10,
+            read_timeout: 30,
+            retries: 3,
+            proxy: None,
+            no_proxy: None,
+            ca_certificates: Vec::new(),
+        }
+    }
+}
+
+impl NetworkSettings {
+    fn from_json(json_data: &json::JsonValue) -> NetworkSettings {
+        let defaults = NetworkSettings::default();
+        NetworkSettings {
+            connect_timeout: json_data["connect_timeout"].as_u64().unwrap_or(defaults.connect_timeout),
+            read_timeout: json_data["read_timeout"].as_u64().unwrap_or(defaults.read_timeout),
+            retries: json_data["retries"].as_u32().unwrap_or(defaults.retries),
+            proxy: json_data["proxy"].as_str().map(String::from),
+            no_proxy: json_data["no_proxy"].as_str().map(String::from),
+            ca_certificates: json_data["ca_certificates"].members().filter_map(|path| path.as_str().map(String::from)).collect(),
+        }
+    }
+
+    fn to_json(&self) -> json::JsonValue {
+        let mut json_data = json::JsonValue::new_object();
+        json_data["connect_timeout"] = self.connect_timeout.into();
+        json_data["read_timeout"] = self.read_timeout.into();
+        json_data["retries"] = self.retries.into();
+        json_data["proxy"] = self.proxy.clone().into();
+        json_data["no_proxy"] = self.no_proxy.clone().into();
+        json_data["ca_certificates"] = self.ca_certificates.clone().into();
+        json_data
+    }
+}
+
+/// What to do when a version has no build for this architecture but one could run emulated.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ArchFallback {
+    /// Report the version as not found.
+    Never,
+    /// Ask before installing the emulated build.
+    Ask,
+    /// Install the emulated build without asking.
+    Always,
+}
+
+impl ArchFallback {
+    fn from_name(name: &str) -> Option<ArchFallback> {
+        match name {
+            "never" => Some(ArchFallback::Never),
+            "ask" => Some(ArchFallback::Ask),
+            "always" => Some(ArchFallback::Always),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            ArchFallback::Never => "never",
+            ArchFallback::Ask => "ask",
+            ArchFallback::Always => "always",
+        }
+    }
+}
+
 pub struct Config {
     pub path: PathBuf,
     pub installed: Vec<Installed>,
-    pub sources: Vec<Source>
+    pub sources: Vec<Source>,
+    pub aliases: Vec<Alias>,
+    pub registries: Vec<Registry>,
+    pub cache_ttl: u64,
+    pub discover_checksums: bool,
+    pub network: NetworkSettings,
+    pub mirrors: Vec<Mirror>,
+    pub arch_fallback: ArchFallback
 }
 
 impl Default for Config {
@@ -43,6 +244,13 @@ impl Config {
                 path: pkit_dir,
                 installed: Vec::new(),
                 sources: Vec::new(),
+                aliases: Vec::new(),
+                registries: Vec::new(),
+                cache_ttl: DEFAULT_CACHE_TTL,
+                discover_checksums: true,
+                network: NetworkSettings::default(),
+                mirrors: Vec::new(),
+                arch_fallback: ArchFallback::Ask,
             };
             config.write().expect("Failed to write initial config");
             config
@@ -70,6 +278,12 @@ impl Config {
                 version: install["version"].to_string(),
                 path: install["path"].to_string(),
                 default: install["default"].as_bool().unwrap_or(false),
+                checksum: install["checksum"].as_str().map(String::from),
+                url: install["url"].as_str().map(String::from),
+                // Entries written before cross-installs existed are for this machine.
+                platform: install["platform"].as_str().unwrap_or(target::get_host_platform()).to_string(),
+                arch: install["arch"].as_str().unwrap_or(target::get_host_arch()).to_string(),
+                emulated: install["emulated"].as_bool().unwrap_or(false),
             });
         }
 
@@ -81,11 +295,53 @@ impl Config {
             });
         }
 
+        let mut aliases = Vec::new();
+        for alias in json_data["aliases"].members() {
+            aliases.push(Alias {
+                language: alias["language"].to_string(),
+                name: alias["name"].to_string(),
+                version: alias["version"].to_string(),
+            });
+        }
+
+        let mut registries = Vec::new();
+        for registry in json_data["registries"].members() {
+            let url = registry["url"].as_str().unwrap_or("");
+            if url.is_empty() {
+                continue;
+            }
+            registries.push(Registry {
+                name: registry["name"].as_str().map(String::from).unwrap_or_else(|| Registry::from_url(url).name),
+                url: url.trim_end_matches('/').to_string(),
+                public_keys: registry["public_keys"].members().filter_map(|key| key.as_str().map(String::from)).collect(),
+                static_index: registry["static"].as_bool().unwrap_or(false),
+                auth: RegistryAuth::from_json(&registry["auth"]),
+            });
+        }
+
+        let mut mirrors = Vec::new();
+        for mirror in json_data["mirrors"].members() {
+            let Some(prefix) = mirror["prefix"].as_str() else {
+                continue;
+            };
+            mirrors.push(Mirror {
+                prefix: prefix.to_string(),
+                urls: mirror["urls"].members().filter_map(|url| url.as_str().map(String::from)).collect(),
+            });
+        }
+
         let pkit_dir = get_pkit_dir()?;
         Ok(Config {
             path: pkit_dir,
             installed,
-            sources
+            sources,
+            aliases,
+            registries,
+            cache_ttl: json_data["cache_ttl"].as_u64().unwrap_or(DEFAULT_CACHE_TTL),
+            discover_checksums: json_data["discover_checksums"].as_bool().unwrap_or(true),
+            arch_fallback: json_data["arch_fallback"].as_str().and_then(ArchFallback::from_name).unwrap_or(ArchFallback::Ask),
+            network: NetworkSettings::from_json(&json_data["network"]),
+            mirrors
         })
     }
 
@@ -93,6 +349,7 @@ impl Config {
         let mut json_data = json::JsonValue::new_object();
         let mut installed = json::JsonValue::new_array();
         let mut sources_array = json::JsonValue::new_array();
+        let mut registries_array = json::JsonValue::new_array();
 
         for install in &self.installed {
             let mut install_json = json::JsonValue::new_object();
@@ -100,6 +357,13 @@ impl Config {
             install_json["version"] = install.version.clone().into();
             install_json["path"] = install.path.clone().into();
             install_json["default"] = install.default.into();
+            install_json["checksum"] = install.checksum.clone().into();
+            install_json["url"] = install.url.clone().into();
+            install_json["platform"] = install.platform.clone().into();
+            install_json["arch"] = install.arch.clone().into();
+            if install.emulated {
+                install_json["emulated"] = true.into();
+            }
             let _ = installed.push(install_json);
         }
 
@@ -110,37 +374,80 @@ impl Config {
             let _ = sources_array.push(source_json);
         }
         
+        let mut aliases_array = json::JsonValue::new_array();
+        for alias in &self.aliases {
+            let mut alias_json = json::JsonValue::new_object();
+            alias_json["language"] = alias.language.clone().into();
+            alias_json["name"] = alias.name.clone().into();
+            alias_json["version"] = alias.version.clone().into();
+            let _ = aliases_array.push(alias_json);
+        }
+
+        for registry in &self.registries {
+            let mut registry_json = json::JsonValue::new_object();
+            registry_json["name"] = registry.name.clone().into();
+            registry_json["url"] = registry.url.clone().into();
+            if !registry.public_keys.is_empty() {
+                registry_json["public_keys"] = registry.public_keys.clone().into();
+            }
+            if registry.static_index {
+                registry_json["static"] = true.into();
+            }
+            if !registry.auth.is_empty() {
+                registry_json["auth"] = registry.auth.to_json();
+            }
+            let _ = registries_array.push(registry_json);
+        }
+
+        json_data["registries"] = registries_array;
+        json_data["cache_ttl"] = self.cache_ttl.into();
+        json_data["discover_checksums"] = self.discover_checksums.into();
+        json_data["arch_fallback"] = self.arch_fallback.name().into();
+        json_data["network"] = self.network.to_json();
+
+        let mut mirrors_array = json::JsonValue::new_array();
+        for mirror in &self.mirrors {
+            let mut mirror_json = json::JsonValue::new_object();
+            mirror_json["prefix"] = mirror.prefix.clone().into();
+            mirror_json["urls"] = mirror.urls.clone().into();
+            let _ = mirrors_array.push(mirror_json);
+        }
+        json_data["mirrors"] = mirrors_array;
         json_data["sources"] = sources_array;
+        json_data["aliases"] = aliases_array;
         json_data["installed"] = installed;
         json_data["path"] = self.path.to_str().unwrap_or("").into();
 
         let config_path = self.path.join("pkit.json");
-        filesystem::write(&config_path, &json_data.pretty(2))
+        filesystem::write_atomic(&config_path, &json_data.pretty(2))
     }
 
-    pub fn add_install(&mut self, language: &str, version: &str, path: &str, default: bool) {
-        if self.get(language, version).is_some() {
-            self.update_install(language, version, path);
-            if default {
-                self.set_default(language, version);
+    pub fn add_install(&mut self, install: Installed) {
+        let existing = self
+            .installed
+            .iter_mut()
+            .find(|existing| existing.is_target(&install.language, &install.version, &install.platform, &install.arch));
+
+        if let Some(existing) = existing {
+            existing.path = install.path.clone();
+            existing.checksum = install.checksum.clone();
+            existing.url = install.url.clone();
+            existing.emulated = install.emulated;
+            if install.default {
+                self.set_default(&install.language, &install.version);
                 return; // set_default already calls write()
             }
         } else {
             // If setting as default, ensure no other version of this language is default
-            if default {
-                for install in &mut self.installed {
-                    if install.language == language {
-                        install.default = false;
+            if install.default {
+                for existing in &mut self.installed {
+                    if existing.language == install.language {
+                        existing.default = false;
                     }
                 }
             }
             
-            self.installed.push(Installed {
-                language: language.to_string(),
-                version: version.to_string(),
-                path: path.to_string(),
-                default,
-            });
+            self.installed.push(install);
         }
         self.write().expect("Failed to save config after add");
     }
@@ -150,10 +457,17 @@ impl Config {
         self.write().expect("Failed to save config after remove");
     }
 
+    /// Removes the install of `version` for one platform and architecture only.
+    pub fn remove_target(&mut self, language: &str, version: &str, platform: &str, arch: &str) {
+        self.installed.retain(|pkg| !pkg.is_target(language, version, platform, arch));
+        self.write().expect("Failed to save config after remove");
+    }
+
+    /// Only native installs can be the default; cross-installs can't run here.
     pub fn set_default(&mut self, language: &str, version: &str) {
         for install in &mut self.installed {
             if install.language == language {
-                install.default = install.version == version;
+                install.default = install.version == version && install.is_native();
             }
         }
         self.write().expect("Failed to save config after set_default");
@@ -163,19 +477,70 @@ impl Config {
         self.installed.iter().find(|&install| install.language == language && install.default)
     }
 
+    /// Returns the native install of `version`.
     pub fn get(&self, language: &str, version: &str) -> Option<&Installed> {
-        self.installed.iter().find(|&install| install.language == language && install.version == version)
+        self.installed.iter().find(|&install| install.language == language && install.version == version && install.is_native())
+    }
+
+    /// Returns the install of `version` for a specific platform and architecture.
+    pub fn get_target(&self, language: &str, version: &str, platform: &str, arch: &str) -> Option<&Installed> {
+        self.installed.iter().find(|&install| install.is_target(language, version, platform, arch))
     }
 
     pub fn update_install(&mut self, language: &str, version: &str, path: &str) {
         for install in &mut self.installed {
-            if install.language == language && install.version == version {
+            if install.language == language && install.version == version && install.is_native() {
                 install.path = path.to_string();
             }
         }
         self.write().expect("Failed to save config after update");
     }
 
+    pub fn get_alias(&self, language: &str, name: &str) -> Option<&Alias> {
+        self.aliases.iter().find(|alias| alias.language == language && alias.name == name)
+    }
+
+    /// Points `name` at `version`, replacing whatever it pointed at before.
+    pub fn set_alias(&mut self, language: &str, name: &str, version: &str) {
+        match self.aliases.iter_mut().find(|alias| alias.language == language && alias.name == name) {
+            Some(alias) => alias.version = version.to_string(),
+            None => self.aliases.push(Alias {
+                language: language.to_string(),
+                name: name.to_string(),
+                version: version.to_string(),
+            }),
+        }
+        self.write().expect("Failed to save config after set_alias");
+    }
+
+    pub fn remove_alias(&mut self, language: &str, name: &str) {
+        self.aliases.retain(|alias| !(alias.language == language && alias.name == name));
+        self.write().expect("Failed to save config after remove_alias");
+    }
+
+    /// Aliases of `language` whose version is no longer installed on this machine.
+    pub fn dangling_aliases(&self, language: &str) -> Vec<&Alias> {
+        self.aliases
+            .iter()
+            .filter(|alias| alias.language == language && self.get(language, &alias.version).is_none())
+            .collect()
+    }
+
+    /// Warns about each dangling alias of `language`. Uninstalling keeps such aliases so they
+    /// can be repointed, which this tells the user how to do.
+    pub fn warn_dangling_aliases(&self, language: &str) {
+        for alias in self.dangling_aliases(language) {
+            print_message(MessageType::Warning(&format!(
+                "Alias '{}' points to {} {}, which is no longer installed (pkit alias {} {} <version>)",
+                alias.name,
+                capitalize_first(language),
+                alias.version,
+                language,
+                alias.name
+            )));
+        }
+    }
+
     pub fn add_path_source(&mut self, name: &str, path: &str) {
         if self.sources.iter().any(|s| s.name == name) {
             eprintln!("Source with name '{}' already exists.", name);
@@ -103,7 +103,7 @@ fn clean_pkit_entries_from_file(config_path: &PathBuf) -> io::Result<()> {
                 Some(None)
             }
         })
-        .filter_map(|x| x)
+        .flatten()
         .collect();
 
     fs::write(config_path, cleaned_lines.join("\n"))?;
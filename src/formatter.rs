@@ -83,13 +83,12 @@ fn visible_length(text: &str) -> usize {
     let mut length = 0;
     let mut chars = text.chars().peekable();
     while let Some(c) = chars.next() {
-        if c == '&' {
-            if let Some(next) = chars.peek() {
-                if matches!(next, '0'..='9' | 'a'..='f' | 'r') {
-                    chars.next(); // consume the color code
-                    continue;
-                }
-            }
+        if c == '&'
+            && let Some(next) = chars.peek()
+            && matches!(next, '0'..='9' | 'a'..='f' | 'r')
+        {
+            chars.next(); // consume the color code
+            continue;
         }
         length += 1;
     }
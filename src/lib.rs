@@ -4,3 +4,7 @@ pub mod commands;
 pub mod formatter;
 pub mod api;
 pub mod cli;
+pub mod project;
+pub mod resolver;
+pub mod suggest;
+pub mod version;
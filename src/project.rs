@@ -0,0 +1,186 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::filesystem;
+
+/// Project file listing language versions under a `[languages]` table.
+pub const PROJECT_FILE: &str = ".pkit.toml";
+/// Plain alternative to [`PROJECT_FILE`] with one `language version` pair per line.
+pub const VERSION_FILE: &str = ".pkit-version";
+
+#[derive(Deserialize)]
+struct ProjectToml {
+    #[serde(default)]
+    languages: BTreeMap<String, String>,
+}
+
+/// The language versions a project asks for, and the file they came from.
+pub struct ProjectFile {
+    pub path: PathBuf,
+    /// `(language, version request)` pairs, sorted by language for [`PROJECT_FILE`] and in line
+    /// order for [`VERSION_FILE`].
+    pub versions: Vec<(String, String)>,
+}
+
+impl ProjectFile {
+    /// The version request for `language`, such as `20.11.0`, `^3.12` or an alias name.
+    pub fn get(&self, language: &str) -> Option<&str> {
+        self.versions
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(language))
+            .map(|(_, version)| version.as_str())
+    }
+
+    pub fn read(path: &Path) -> Result<ProjectFile, String> {
+        let contents = filesystem::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let versions = if path.file_name().is_some_and(|name| name == PROJECT_FILE) {
+            parse_toml(&contents)
+        } else {
+            parse_version_file(&contents)
+        };
+        let versions = versions.map_err(|e| format!("Invalid {}: {}", path.display(), e))?;
+        Ok(ProjectFile { path: path.to_path_buf(), versions })
+    }
+}
+
+fn parse_toml(contents: &str) -> Result<Vec<(String, String)>, String> {
+    let project: ProjectToml = toml::from_str(contents).map_err(|e| e.message().to_string())?;
+    Ok(project.languages.into_iter().collect())
+}
+
+/// Reads lines such as `node 20.11.0` or `python@3.12`; `#` starts a comment.
+fn parse_version_file(contents: &str) -> Result<Vec<(String, String)>, String> {
+    let mut versions = Vec::new();
+    for (number, line) in contents.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let pair = line.split_once('@').or_else(|| line.split_once(char::is_whitespace));
+        match pair {
+            Some((language, version)) if !language.trim().is_empty() && !version.trim().is_empty() => {
+                versions.push((language.trim().to_string(), version.trim().to_string()));
+            }
+            _ => return Err(format!("line {} is not in the form 'language version'", number + 1)),
+        }
+    }
+    Ok(versions)
+}
+
+/// Looks for a project file in `start` and each of its parents, returning the nearest one.
+/// When a directory holds both, [`PROJECT_FILE`] wins.
+pub fn find(start: &Path) -> Result<Option<ProjectFile>, String> {
+    for dir in start.ancestors() {
+        for name in [PROJECT_FILE, VERSION_FILE] {
+            let path = dir.join(name);
+            if path.is_file() {
+                return ProjectFile::read(&path).map(Some);
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// [`find`] starting from the current directory.
+pub fn find_from_current_dir() -> Result<Option<ProjectFile>, String> {
+    match std::env::current_dir() {
+        Ok(dir) => find(&dir),
+        Err(_) => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// A fresh directory under the system temp dir, unique to this test.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pkit-project-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn pairs(versions: &[(&str, &str)]) -> Vec<(String, String)> {
+        versions.iter().map(|(language, version)| (language.to_string(), version.to_string())).collect()
+    }
+
+    #[test]
+    fn parses_both_version_file_forms() {
+        let contents = "node 20.11.0\npython@3.12\n  go   1.22.0  \n";
+        assert_eq!(parse_version_file(contents).unwrap(), pairs(&[("node", "20.11.0"), ("python", "3.12"), ("go", "1.22.0")]));
+    }
+
+    #[test]
+    fn skips_comments_and_blank_lines() {
+        let contents = "# tools for this repo\n\nnode ^20 # matches CI\n   \n# python 3.11\njava@21\n";
+        assert_eq!(parse_version_file(contents).unwrap(), pairs(&[("node", "^20"), ("java", "21")]));
+    }
+
+    #[test]
+    fn rejects_lines_without_a_version() {
+        let error = parse_version_file("node 20\npython\n").unwrap_err();
+        assert!(error.contains("line 2"), "{}", error);
+        assert!(parse_version_file("@3.12").is_err());
+        assert!(parse_version_file("node@").is_err());
+    }
+
+    #[test]
+    fn parses_the_languages_table() {
+        let contents = "[languages]\npython = \"3.12\"\nnode = \"^20\"\n";
+        assert_eq!(parse_toml(contents).unwrap(), pairs(&[("node", "^20"), ("python", "3.12")]));
+        assert!(parse_toml("").unwrap().is_empty());
+        assert!(parse_toml("[languages]\nnode = 20\n").is_err());
+    }
+
+    #[test]
+    fn looks_up_languages_case_insensitively() {
+        let project = ProjectFile { path: PathBuf::from(VERSION_FILE), versions: pairs(&[("Node", "20")]) };
+        assert_eq!(project.get("node"), Some("20"));
+        assert_eq!(project.get("python"), None);
+    }
+
+    #[test]
+    fn finds_the_nearest_file_in_parent_directories() {
+        let root = temp_dir("nearest");
+        let nested = root.join("app").join("src");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(VERSION_FILE), "node 18\n").unwrap();
+
+        let found = find(&nested).unwrap().unwrap();
+        assert_eq!(found.path, root.join(VERSION_FILE));
+        assert_eq!(found.get("node"), Some("18"));
+
+        fs::write(root.join("app").join(VERSION_FILE), "node 20\n").unwrap();
+        assert_eq!(find(&nested).unwrap().unwrap().get("node"), Some("20"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn prefers_the_toml_file_in_the_same_directory() {
+        let root = temp_dir("prefers-toml");
+        fs::write(root.join(VERSION_FILE), "node 18\n").unwrap();
+        fs::write(root.join(PROJECT_FILE), "[languages]\nnode = \"20\"\n").unwrap();
+
+        let found = find(&root).unwrap().unwrap();
+        assert_eq!(found.path, root.join(PROJECT_FILE));
+        assert_eq!(found.get("node"), Some("20"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn reports_invalid_files_with_their_path() {
+        let root = temp_dir("invalid");
+        fs::write(root.join(VERSION_FILE), "node\n").unwrap();
+
+        let error = find(&root).err().unwrap();
+        assert!(error.contains(VERSION_FILE) && error.contains("line 1"), "{}", error);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
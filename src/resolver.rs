@@ -0,0 +1,548 @@
+use std::cmp::Ordering;
+use std::path::PathBuf;
+
+use crate::api;
+use crate::filesystem::config::{Config, Installed};
+use crate::project::ProjectFile;
+use crate::version;
+
+/// What the user asked for when naming a version on the command line.
+#[derive(Debug, PartialEq)]
+pub enum VersionRequest {
+    /// The newest release (prereleases only when nothing else exists).
+    Latest,
+    /// The newest long-term-support release.
+    Lts,
+    /// The newest release on the stable channel.
+    Stable,
+    /// A partial version such as `18` or `18.2`, or a wildcard such as `18.x`.
+    Partial(Vec<u64>),
+    /// One or more comparator sets joined by `||`, such as `^18.2` or `>=3.11 <3.13`.
+    Range(Vec<Vec<Comparator>>),
+    /// Anything else is matched literally.
+    Exact(String),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Operator {
+    Equal,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Comparator {
+    pub operator: Operator,
+    pub version: Vec<u64>,
+}
+
+impl Comparator {
+    fn new(operator: Operator, version: Vec<u64>) -> Comparator {
+        Comparator { operator, version }
+    }
+
+    fn matches(&self, version: &[u64]) -> bool {
+        let ordering = version::compare_release(version, &self.version);
+        match self.operator {
+            Operator::Equal => ordering == Ordering::Equal,
+            Operator::Greater => ordering == Ordering::Greater,
+            Operator::GreaterOrEqual => ordering != Ordering::Less,
+            Operator::Less => ordering == Ordering::Less,
+            Operator::LessOrEqual => ordering != Ordering::Greater,
+        }
+    }
+}
+
+/// A version offered to the resolver, with whatever release metadata is known about it.
+pub struct Candidate<'a> {
+    pub version: &'a str,
+    pub lts: bool,
+    pub channel: Option<&'a str>,
+}
+
+impl<'a> Candidate<'a> {
+    pub fn new(version: &'a str) -> Candidate<'a> {
+        Candidate { version, lts: false, channel: None }
+    }
+
+    /// A registry build, with its LTS flag and channel.
+    pub fn from_version(version: &'a api::Version) -> Candidate<'a> {
+        Candidate { version: &version.version, lts: version.lts, channel: version.channel.as_deref() }
+    }
+}
+
+/// Parses `1`, `1.2`, `1.x` or `1.2.*` into the numbers that must match.
+fn parse_partial(input: &str) -> Option<Vec<u64>> {
+    let input = input.trim_start_matches('v');
+    let mut parts = Vec::new();
+    for part in input.split('.') {
+        match part {
+            "x" | "X" | "*" => break,
+            _ => parts.push(part.parse().ok()?),
+        }
+    }
+    Some(parts)
+}
+
+/// Expands one range term (`^1.2`, `~1.2.3`, `>=3.11`, `1.x`, ...) into comparators.
+fn parse_term(term: &str) -> Option<Vec<Comparator>> {
+    let operators = [
+        (">=", Operator::GreaterOrEqual),
+        ("<=", Operator::LessOrEqual),
+        (">", Operator::Greater),
+        ("<", Operator::Less),
+        ("=", Operator::Equal),
+    ];
+    for (prefix, operator) in operators {
+        if let Some(rest) = term.strip_prefix(prefix) {
+            return Some(vec![Comparator::new(operator, parse_partial(rest.trim())?)]);
+        }
+    }
+
+    if let Some(rest) = term.strip_prefix('^') {
+        let base = parse_partial(rest)?;
+        // ^1.2.3 allows minor and patch updates; ^0.2.3 only patch updates.
+        let significant = base.iter().position(|part| *part != 0).unwrap_or(base.len().saturating_sub(1));
+        let mut upper: Vec<u64> = base.iter().take(significant + 1).copied().collect();
+        if let Some(last) = upper.last_mut() {
+            *last += 1;
+        }
+        return Some(vec![
+            Comparator::new(Operator::GreaterOrEqual, base),
+            Comparator::new(Operator::Less, upper),
+        ]);
+    }
+
+    if let Some(rest) = term.strip_prefix('~') {
+        let base = parse_partial(rest)?;
+        // ~1.2.3 allows patch updates; ~1 allows minor updates.
+        let mut upper: Vec<u64> = base.iter().take(if base.len() > 1 { 2 } else { 1 }).copied().collect();
+        if let Some(last) = upper.last_mut() {
+            *last += 1;
+        }
+        return Some(vec![
+            Comparator::new(Operator::GreaterOrEqual, base),
+            Comparator::new(Operator::Less, upper),
+        ]);
+    }
+
+    let base = parse_partial(term)?;
+    let mut upper = base.clone();
+    match upper.last_mut() {
+        Some(last) => *last += 1,
+        None => return Some(Vec::new()), // `*` matches everything
+    }
+    Some(vec![
+        Comparator::new(Operator::GreaterOrEqual, base),
+        Comparator::new(Operator::Less, upper),
+    ])
+}
+
+impl VersionRequest {
+    pub fn parse(input: &str) -> VersionRequest {
+        let input = input.trim();
+        match input.to_lowercase().as_str() {
+            "latest" => return VersionRequest::Latest,
+            "lts" => return VersionRequest::Lts,
+            "stable" => return VersionRequest::Stable,
+            _ => {}
+        }
+
+        if !input.contains(['^', '~', '<', '>', '=', ' ', '|'])
+            && let Some(parts) = parse_partial(input)
+        {
+            return VersionRequest::Partial(parts);
+        }
+
+        let sets: Option<Vec<Vec<Comparator>>> = input
+            .split("||")
+            .map(|set| {
+                let mut comparators = Vec::new();
+                for term in normalize_range(set).split_whitespace() {
+                    comparators.extend(parse_term(term)?);
+                }
+                Some(comparators)
+            })
+            .collect();
+
+        match sets {
+            Some(sets) if !sets.is_empty() => VersionRequest::Range(sets),
+            _ => VersionRequest::Exact(input.to_string()),
+        }
+    }
+
+    /// Whether `version` satisfies a partial version or range. Keywords match nothing here;
+    /// they pick from a whole list in [`resolve`].
+    pub fn matches(&self, version: &str) -> bool {
+        match self {
+            VersionRequest::Exact(exact) => version::same(version, exact),
+            VersionRequest::Partial(parts) => {
+                let candidate = version::release(version);
+                !version::is_prerelease(version) && candidate.len() >= parts.len() && candidate.starts_with(parts)
+            }
+            VersionRequest::Range(sets) => {
+                let candidate = version::release(version);
+                !version::is_prerelease(version)
+                    && sets.iter().any(|set| set.iter().all(|comparator| comparator.matches(&candidate)))
+            }
+            VersionRequest::Latest | VersionRequest::Lts | VersionRequest::Stable => false,
+        }
+    }
+}
+
+/// Joins operators to their version (`>= 3.11` becomes `>=3.11`) so terms split on spaces.
+fn normalize_range(set: &str) -> String {
+    let mut normalized = String::new();
+    let mut pending_operator = false;
+    for token in set.split_whitespace() {
+        if !normalized.is_empty() && !pending_operator {
+            normalized.push(' ');
+        }
+        normalized.push_str(token);
+        pending_operator = token.chars().all(|c| matches!(c, '<' | '>' | '=' | '^' | '~'));
+    }
+    normalized
+}
+
+fn newest<'a>(candidates: &[Candidate<'a>], filter: &dyn Fn(&Candidate) -> bool) -> Option<&'a str> {
+    candidates
+        .iter()
+        .filter(|candidate| filter(candidate))
+        .max_by(|a, b| version::compare(a.version, b.version))
+        .map(|candidate| candidate.version)
+}
+
+/// The newest release sharing the first `depth` release numbers with `current`, so depth 1
+/// stays on the same major version and depth 2 on the same minor version.
+pub fn newest_in_line<'a>(current: &str, depth: usize, candidates: &[Candidate<'a>]) -> Option<&'a str> {
+    let mut line = version::release(current);
+    line.truncate(depth);
+    if line.is_empty() {
+        return None;
+    }
+    let request = VersionRequest::Partial(line);
+    newest(candidates, &|c| request.matches(c.version))
+}
+
+/// Picks the version that best satisfies `request` from `candidates`.
+///
+/// A literal match always wins, so `pkit install python 3.13.0rc1` works as before.
+/// Otherwise the newest matching version is chosen; prereleases are only chosen by
+/// `latest` when there is nothing else.
+pub fn resolve<'a>(request: &str, candidates: &[Candidate<'a>]) -> Option<&'a str> {
+    if let Some(candidate) = candidates.iter().find(|candidate| candidate.version == request.trim()) {
+        return Some(candidate.version);
+    }
+
+    let newest = |filter: &dyn Fn(&Candidate) -> bool| newest(candidates, filter);
+
+    match VersionRequest::parse(request) {
+        VersionRequest::Latest => newest(&|c| !version::is_prerelease(c.version)).or_else(|| newest(&|_| true)),
+        VersionRequest::Lts => newest(&|c| c.lts),
+        VersionRequest::Stable => {
+            newest(&|c| !version::is_prerelease(c.version) && c.channel.is_none_or(|channel| channel == "stable"))
+        }
+        parsed => newest(&|c| parsed.matches(c.version)),
+    }
+}
+
+/// Whether `name` can be used for an alias without shadowing a version, range or keyword.
+pub fn is_alias_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && matches!(VersionRequest::parse(name), VersionRequest::Exact(_))
+        && version::ParsedVersion::parse(name).is_none()
+}
+
+/// Resolves `request` against the installed versions of `language` that run on this machine.
+///
+/// Aliases set with `pkit alias` are looked up first. Installed versions don't record
+/// whether they are LTS releases, so `lts` is rejected here with an explanation rather
+/// than silently picking something else.
+pub fn resolve_installed<'a>(config: &'a Config, language: &str, request: &str) -> Result<Option<&'a Installed>, String> {
+    if let Some(alias) = config.get_alias(language, request) {
+        return match config.get(language, &alias.version) {
+            Some(installed) => Ok(Some(installed)),
+            None => Err(format!(
+                "Alias '{}' points to {} {}, which is not installed; repoint it with 'pkit alias {} {} <version>'",
+                alias.name, language, alias.version, language, alias.name
+            )),
+        };
+    }
+
+    if VersionRequest::parse(request) == VersionRequest::Lts {
+        return Err(format!(
+            "'lts' can only be resolved against the registry; name a version of {} instead",
+            language
+        ));
+    }
+
+    let installed: Vec<&Installed> = config
+        .installed
+        .iter()
+        .filter(|install| install.language == language && install.is_native())
+        .collect();
+    let candidates: Vec<Candidate> = installed.iter().map(|install| Candidate::new(&install.version)).collect();
+
+    Ok(resolve(request, &candidates).and_then(|version| installed.into_iter().find(|install| install.version == version)))
+}
+
+/// Where the active version of a language was decided.
+pub enum ActiveSource {
+    /// A project file in the current directory or one of its parents.
+    Project(PathBuf),
+    /// `pkit switch` in this shell session.
+    Session,
+    /// The global default set with `pkit default`.
+    Default,
+}
+
+/// The version of a language that applies here, and why.
+pub struct Active<'a> {
+    pub language: String,
+    /// What was asked for; a project file may name a partial version, range or alias.
+    pub request: String,
+    /// `None` when the requested version isn't installed.
+    pub installed: Option<&'a Installed>,
+    pub source: ActiveSource,
+}
+
+/// Environment variable `pkit switch` sets in the session script, e.g. `PKIT_SESSION_NODE`.
+pub fn session_variable(language: &str) -> String {
+    let name: String = language
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    format!("PKIT_SESSION_{}", name)
+}
+
+/// Decides which version of `language` is active: a project file wins over a session
+/// switch, which wins over the global default.
+pub fn resolve_active<'a>(config: &'a Config, project: Option<&ProjectFile>, language: &str) -> Option<Active<'a>> {
+    let session = std::env::var(session_variable(language)).ok().filter(|version| !version.is_empty());
+    choose_active(config, project, language, session)
+}
+
+/// [`resolve_active`] with the session's version passed in instead of read from the environment.
+fn choose_active<'a>(config: &'a Config, project: Option<&ProjectFile>, language: &str, session: Option<String>) -> Option<Active<'a>> {
+    if let Some(project) = project
+        && let Some(request) = project.get(language)
+    {
+        return Some(Active {
+            language: language.to_string(),
+            request: request.to_string(),
+            installed: resolve_installed(config, language, request).ok().flatten(),
+            source: ActiveSource::Project(project.path.clone()),
+        });
+    }
+
+    if let Some(version) = session {
+        return Some(Active {
+            language: language.to_string(),
+            installed: config.get(language, &version),
+            request: version,
+            source: ActiveSource::Session,
+        });
+    }
+
+    config.get_default(language).map(|installed| Active {
+        language: language.to_string(),
+        request: installed.version.clone(),
+        installed: Some(installed),
+        source: ActiveSource::Default,
+    })
+}
+
+/// The active version of every language that is installed or named by the project file.
+pub fn resolve_all_active<'a>(config: &'a Config, project: Option<&ProjectFile>) -> Vec<Active<'a>> {
+    let mut languages: Vec<String> = config.installed.iter().map(|install| install.language.clone()).collect();
+    if let Some(project) = project {
+        languages.extend(project.versions.iter().map(|(language, _)| language.to_lowercase()));
+    }
+    languages.sort();
+    languages.dedup();
+
+    languages.iter().filter_map(|language| resolve_active(config, project, language)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VERSIONS: [&str; 9] = ["0.2.3", "0.2.9", "0.3.0", "1.2.3", "1.2.9", "1.3.0", "1.9.9", "2.0.0", "2.1.0-rc.1"];
+
+    /// Every version in [`VERSIONS`] that `request` accepts.
+    fn matching(request: &str) -> Vec<&'static str> {
+        let request = VersionRequest::parse(request);
+        VERSIONS.into_iter().filter(|v| request.matches(v)).collect()
+    }
+
+    #[test]
+    fn parses_keywords_and_partials() {
+        assert_eq!(VersionRequest::parse("latest"), VersionRequest::Latest);
+        assert_eq!(VersionRequest::parse("LTS"), VersionRequest::Lts);
+        assert_eq!(VersionRequest::parse("stable"), VersionRequest::Stable);
+        assert_eq!(VersionRequest::parse("18"), VersionRequest::Partial(vec![18]));
+        assert_eq!(VersionRequest::parse("v18.2"), VersionRequest::Partial(vec![18, 2]));
+        assert_eq!(VersionRequest::parse("18.x"), VersionRequest::Partial(vec![18]));
+        assert_eq!(VersionRequest::parse("3.13.0rc1"), VersionRequest::Exact("3.13.0rc1".to_string()));
+    }
+
+    #[test]
+    fn partial_versions_match_their_line() {
+        assert_eq!(matching("1"), ["1.2.3", "1.2.9", "1.3.0", "1.9.9"]);
+        assert_eq!(matching("1.2"), ["1.2.3", "1.2.9"]);
+        assert_eq!(matching("1.2.*"), ["1.2.3", "1.2.9"]);
+        assert_eq!(matching("2"), ["2.0.0"]);
+    }
+
+    #[test]
+    fn caret_allows_changes_right_of_the_first_nonzero_number() {
+        assert_eq!(matching("^1.2.3"), ["1.2.3", "1.2.9", "1.3.0", "1.9.9"]);
+        assert_eq!(matching("^0.2.3"), ["0.2.3", "0.2.9"]);
+        assert_eq!(matching("^1"), ["1.2.3", "1.2.9", "1.3.0", "1.9.9"]);
+    }
+
+    #[test]
+    fn tilde_allows_patch_changes() {
+        assert_eq!(matching("~1.2.3"), ["1.2.3", "1.2.9"]);
+        assert_eq!(matching("~1.2"), ["1.2.3", "1.2.9"]);
+        assert_eq!(matching("~1"), ["1.2.3", "1.2.9", "1.3.0", "1.9.9"]);
+    }
+
+    #[test]
+    fn comparison_operators() {
+        assert_eq!(matching(">=1.9.9"), ["1.9.9", "2.0.0"]);
+        assert_eq!(matching(">1.9.9"), ["2.0.0"]);
+        assert_eq!(matching("<=0.2.9"), ["0.2.3", "0.2.9"]);
+        assert_eq!(matching("<0.2.9"), ["0.2.3"]);
+        assert_eq!(matching("=1.3"), ["1.3.0"]);
+    }
+
+    #[test]
+    fn combined_and_alternative_ranges() {
+        assert_eq!(matching(">=1.2.9 <1.9"), ["1.2.9", "1.3.0"]);
+        assert_eq!(matching(">= 1.2.9 < 1.9"), ["1.2.9", "1.3.0"]);
+        assert_eq!(matching("0.3 || 2"), ["0.3.0", "2.0.0"]);
+        assert_eq!(matching("1.x || >=2"), ["1.2.3", "1.2.9", "1.3.0", "1.9.9", "2.0.0"]);
+        assert_eq!(matching("*").len(), VERSIONS.len() - 1);
+    }
+
+    #[test]
+    fn resolves_the_newest_match() {
+        let candidates: Vec<Candidate> = VERSIONS.iter().map(|v| Candidate::new(v)).collect();
+        assert_eq!(resolve("^1.2", &candidates), Some("1.9.9"));
+        assert_eq!(resolve("~1.2", &candidates), Some("1.2.9"));
+        assert_eq!(resolve("latest", &candidates), Some("2.0.0"));
+        assert_eq!(resolve("2.1.0-rc.1", &candidates), Some("2.1.0-rc.1"));
+        assert_eq!(resolve("3", &candidates), None);
+    }
+
+    #[test]
+    fn resolves_lts_and_stable_from_metadata() {
+        let candidates = [
+            Candidate { version: "20.11.0", lts: true, channel: None },
+            Candidate { version: "21.6.0", lts: false, channel: Some("current") },
+            Candidate { version: "18.19.0", lts: true, channel: Some("stable") },
+        ];
+        assert_eq!(resolve("lts", &candidates), Some("20.11.0"));
+        assert_eq!(resolve("stable", &candidates), Some("20.11.0"));
+        assert_eq!(resolve("latest", &candidates), Some("21.6.0"));
+    }
+
+    #[test]
+    fn alias_names_must_not_look_like_versions() {
+        assert!(is_alias_name("work"));
+        assert!(is_alias_name("legacy-app"));
+        assert!(is_alias_name("node_ci2"));
+        assert!(!is_alias_name("20"));
+        assert!(!is_alias_name("v20"));
+        assert!(!is_alias_name("go1.22"));
+        assert!(!is_alias_name("x"));
+        assert!(!is_alias_name("latest"));
+        assert!(!is_alias_name("Stable"));
+        assert!(!is_alias_name("^20"));
+        assert!(!is_alias_name("my alias"));
+        assert!(!is_alias_name("_work"));
+    }
+
+    fn install(version: &str, default: bool) -> Installed {
+        Installed {
+            language: "node".to_string(),
+            version: version.to_string(),
+            path: format!("/pkit/bin/node/{}", version),
+            default,
+            checksum: None,
+            url: None,
+            platform: crate::api::target::get_host_platform().to_string(),
+            arch: crate::api::target::get_host_arch().to_string(),
+            emulated: false,
+        }
+    }
+
+    fn config(installed: Vec<Installed>) -> Config {
+        Config {
+            path: PathBuf::new(),
+            installed,
+            sources: Vec::new(),
+            aliases: Vec::new(),
+            registries: Vec::new(),
+            cache_ttl: 0,
+            discover_checksums: false,
+            network: Default::default(),
+            mirrors: Vec::new(),
+            arch_fallback: crate::filesystem::config::ArchFallback::Never,
+        }
+    }
+
+    fn project(versions: &[(&str, &str)]) -> ProjectFile {
+        ProjectFile {
+            path: PathBuf::from("/work/app/.pkit.toml"),
+            versions: versions.iter().map(|(language, version)| (language.to_string(), version.to_string())).collect(),
+        }
+    }
+
+    #[test]
+    fn project_file_wins_over_session_and_default() {
+        let config = config(vec![install("18.19.0", true), install("20.10.0", false), install("20.11.0", false)]);
+        let project = project(&[("node", "^20")]);
+
+        let active = choose_active(&config, Some(&project), "node", Some("20.10.0".to_string())).unwrap();
+        assert_eq!(active.request, "^20");
+        assert_eq!(active.installed.map(|i| i.version.as_str()), Some("20.11.0"));
+        assert!(matches!(active.source, ActiveSource::Project(path) if path == project.path));
+    }
+
+    #[test]
+    fn session_wins_over_default() {
+        let config = config(vec![install("18.19.0", true), install("20.10.0", false)]);
+        let project = project(&[("python", "3.12")]);
+
+        let active = choose_active(&config, Some(&project), "node", Some("20.10.0".to_string())).unwrap();
+        assert_eq!(active.installed.map(|i| i.version.as_str()), Some("20.10.0"));
+        assert!(matches!(active.source, ActiveSource::Session));
+    }
+
+    #[test]
+    fn falls_back_to_the_default() {
+        let config = config(vec![install("18.19.0", true), install("20.10.0", false)]);
+
+        let active = choose_active(&config, None, "node", None).unwrap();
+        assert_eq!(active.request, "18.19.0");
+        assert!(matches!(active.source, ActiveSource::Default));
+        assert!(choose_active(&config, None, "python", None).is_none());
+    }
+
+    #[test]
+    fn reports_project_versions_that_are_not_installed() {
+        let config = config(vec![install("18.19.0", true)]);
+        let project = project(&[("node", "22")]);
+
+        let active = choose_active(&config, Some(&project), "node", None).unwrap();
+        assert_eq!(active.request, "22");
+        assert!(active.installed.is_none());
+        assert!(matches!(active.source, ActiveSource::Project(_)));
+    }
+}
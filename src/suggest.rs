@@ -0,0 +1,83 @@
+/// How many suggestions are offered at most.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Levenshtein distance between `a` and `b`, ignoring case.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, y) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(x != y);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// The candidates closest to `input`, nearest first.
+///
+/// A candidate qualifies when it is within a third of its length of `input` (at least one
+/// edit), so `nod` suggests `node` but `java` doesn't suggest `go`.
+pub fn closest<'a>(input: &str, candidates: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut scored: Vec<(usize, &str)> = Vec::new();
+    for candidate in candidates {
+        if scored.iter().any(|(_, seen)| *seen == candidate) {
+            continue;
+        }
+        let distance = edit_distance(input, candidate);
+        let limit = (candidate.chars().count().max(input.chars().count()) / 3).max(1);
+        if distance > 0 && distance <= limit {
+            scored.push((distance, candidate));
+        }
+    }
+
+    scored.sort_by_key(|(distance, _)| *distance);
+    scored.into_iter().take(MAX_SUGGESTIONS).map(|(_, candidate)| candidate.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_single_character_edits() {
+        assert_eq!(edit_distance("node", "node"), 0);
+        assert_eq!(edit_distance("nod", "node"), 1);
+        assert_eq!(edit_distance("nose", "node"), 1);
+        assert_eq!(edit_distance("pyhton", "python"), 2);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "go"), 2);
+    }
+
+    #[test]
+    fn ignores_case() {
+        assert_eq!(edit_distance("Node", "node"), 0);
+        assert_eq!(edit_distance("PYTHON", "pythn"), 1);
+    }
+
+    #[test]
+    fn suggests_close_candidates_nearest_first() {
+        let languages = ["node", "python", "go", "java", "deno"];
+        assert_eq!(closest("nod", languages.into_iter()), ["node"]);
+        assert_eq!(closest("pyhton", languages.into_iter()), ["python"]);
+        assert_eq!(closest("pythn", ["pythons", "python"].into_iter()), ["python", "pythons"]);
+    }
+
+    #[test]
+    fn skips_exact_distant_and_repeated_candidates() {
+        let languages = ["node", "node", "go", "java"];
+        assert!(closest("node", ["node"].into_iter()).is_empty());
+        assert!(closest("java", ["go"].into_iter()).is_empty());
+        assert_eq!(closest("nodd", languages.into_iter()), ["node"]);
+    }
+
+    #[test]
+    fn limits_the_number_of_suggestions() {
+        let versions = ["20.11.0", "20.11.1", "20.11.2", "20.11.3", "20.11.4"];
+        assert_eq!(closest("20.11.9", versions.into_iter()).len(), MAX_SUGGESTIONS);
+    }
+}
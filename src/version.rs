@@ -0,0 +1,336 @@
+use std::cmp::Ordering;
+
+/// One dot- or dash-separated piece of a prerelease tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Identifier {
+    Number(u64),
+    /// A word with its rank among the well-known prerelease stages, so `dev < alpha < beta < rc`.
+    Word(u8, String),
+}
+
+impl Ord for Identifier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Identifier::Number(a), Identifier::Number(b)) => a.cmp(b),
+            // Semver: numeric identifiers sort before alphanumeric ones.
+            (Identifier::Number(_), Identifier::Word(..)) => Ordering::Less,
+            (Identifier::Word(..), Identifier::Number(_)) => Ordering::Greater,
+            (Identifier::Word(rank_a, a), Identifier::Word(rank_b, b)) => rank_a.cmp(rank_b).then_with(|| a.cmp(b)),
+        }
+    }
+}
+
+impl PartialOrd for Identifier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn word_rank(word: &str) -> u8 {
+    match word {
+        "dev" | "snapshot" => 0,
+        "a" | "alpha" | "ea" => 1,
+        "b" | "beta" => 2,
+        "c" | "rc" | "pre" | "preview" => 3,
+        _ => 2,
+    }
+}
+
+/// A version parsed into the parts that decide its order.
+///
+/// Understands semver (`1.2.3-beta.1+build`), PEP 440 (`1!3.13.0rc1`, `3.12.0.post1`,
+/// `3.13.0.dev2`), Java (`21.0.2+13`, `1.8.0_392`, `22-ea+5`) and Go (`go1.22.0`, `go1.22rc1`).
+#[derive(Debug, Clone)]
+pub struct ParsedVersion {
+    epoch: u64,
+    release: Vec<u64>,
+    /// Empty for final releases.
+    pre: Vec<Identifier>,
+    post: Option<u64>,
+    /// Build metadata such as Java's `+13` or `+13-LTS`, compared like a prerelease tag
+    /// so that different builds of one release stay distinct.
+    build: Vec<Identifier>,
+}
+
+/// Splits a tag such as `rc1`, `-beta.2` or `.post1` into words and numbers.
+fn tokenize(tag: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut current = String::new();
+
+    for c in tag.chars() {
+        if matches!(c, '.' | '-' | '_') {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if let Some(last) = current.chars().last()
+            && last.is_ascii_digit() != c.is_ascii_digit()
+        {
+            tokens.push(std::mem::take(&mut current));
+        }
+        current.push(c.to_ascii_lowercase());
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+impl ParsedVersion {
+    /// Parses `input`, returning `None` when it doesn't start with a numeric release.
+    pub fn parse(input: &str) -> Option<ParsedVersion> {
+        let mut rest = input.trim();
+        for prefix in ["go", "jdk-", "v", "V"] {
+            rest = rest.strip_prefix(prefix).unwrap_or(rest);
+        }
+
+        let mut epoch = 0;
+        if let Some((value, remainder)) = rest.split_once('!')
+            && let Ok(value) = value.parse()
+        {
+            epoch = value;
+            rest = remainder;
+        }
+
+        let (rest, build) = match rest.split_once('+') {
+            Some((rest, build)) => (rest, build),
+            None => (rest, ""),
+        };
+
+        // The release is the leading run of dot-separated numbers (`_` as in Java's `1.8.0_392`).
+        let mut release: Vec<u64> = Vec::new();
+        let mut tag = rest;
+        loop {
+            let digits = tag.chars().take_while(|c| c.is_ascii_digit()).count();
+            if digits == 0 {
+                break;
+            }
+            release.push(tag[..digits].parse().ok()?);
+            tag = &tag[digits..];
+
+            let next_is_number = tag.chars().nth(1).is_some_and(|c| c.is_ascii_digit());
+            match tag.chars().next() {
+                Some('.') | Some('_') if next_is_number => tag = &tag[1..],
+                _ => break,
+            }
+        }
+        if release.is_empty() {
+            return None;
+        }
+
+        let identifier = |token: String| match token.parse() {
+            Ok(number) => Identifier::Number(number),
+            Err(_) => Identifier::Word(word_rank(&token), token),
+        };
+
+        let mut pre: Vec<Identifier> = Vec::new();
+        let mut post = None;
+        let mut tokens = tokenize(tag).into_iter().peekable();
+        while let Some(token) = tokens.next() {
+            if matches!(token.as_str(), "post" | "rev" | "r") {
+                post = Some(tokens.next_if(|t| t.parse::<u64>().is_ok()).and_then(|t| t.parse().ok()).unwrap_or(0));
+            } else {
+                pre.push(identifier(token));
+            }
+        }
+
+        let build = tokenize(build).into_iter().map(identifier).collect();
+
+        Some(ParsedVersion { epoch, release, pre, post, build })
+    }
+
+    /// The numeric release, e.g. `[3, 13, 0]` for `3.13.0rc1`.
+    pub fn release(&self) -> &[u64] {
+        &self.release
+    }
+
+    /// Whether this is an alpha, beta, release candidate, dev build or similar.
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+}
+
+/// Compares release numbers, treating missing trailing components as zero.
+pub fn compare_release(a: &[u64], b: &[u64]) -> Ordering {
+    let length = a.len().max(b.len());
+    (0..length)
+        .map(|i| a.get(i).unwrap_or(&0).cmp(b.get(i).unwrap_or(&0)))
+        .find(|ordering| *ordering != Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
+
+impl Ord for ParsedVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.epoch
+            .cmp(&other.epoch)
+            .then_with(|| compare_release(&self.release, &other.release))
+            // A prerelease comes before the final release it leads up to.
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre.cmp(&other.pre),
+            })
+            .then_with(|| self.post.cmp(&other.post))
+            .then_with(|| self.build.cmp(&other.build))
+    }
+}
+
+/// Equal when they sort the same, so `1.0` equals `1.0.0`.
+impl PartialEq for ParsedVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ParsedVersion {}
+
+impl PartialOrd for ParsedVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders strings the way people read them: `file2` before `file10`.
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    fn chunks(s: &str) -> Vec<(bool, String)> {
+        let mut chunks: Vec<(bool, String)> = Vec::new();
+        for c in s.chars() {
+            let digit = c.is_ascii_digit();
+            match chunks.last_mut() {
+                Some((is_digit, chunk)) if *is_digit == digit => chunk.push(c),
+                _ => chunks.push((digit, c.to_string())),
+            }
+        }
+        chunks
+    }
+
+    let (a_chunks, b_chunks) = (chunks(a), chunks(b));
+    for (x, y) in a_chunks.iter().zip(b_chunks.iter()) {
+        let ordering = match (x, y) {
+            ((true, x), (true, y)) => {
+                let (x, y) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                x.len().cmp(&y.len()).then_with(|| x.cmp(y))
+            }
+            ((_, x), (_, y)) => x.to_lowercase().cmp(&y.to_lowercase()),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a_chunks.len().cmp(&b_chunks.len()).then_with(|| a.cmp(b))
+}
+
+/// Orders two version strings of any supported scheme, oldest first.
+///
+/// Versions that can't be parsed fall back to natural order.
+pub fn compare(a: &str, b: &str) -> Ordering {
+    match (ParsedVersion::parse(a), ParsedVersion::parse(b)) {
+        (Some(x), Some(y)) => x.cmp(&y).then_with(|| natural_cmp(a, b)),
+        _ => natural_cmp(a, b),
+    }
+}
+
+/// Whether two strings name the same version, such as `v20.11.0` and `20.11.0`.
+pub fn same(a: &str, b: &str) -> bool {
+    if a == b {
+        return true;
+    }
+    match (ParsedVersion::parse(a), ParsedVersion::parse(b)) {
+        (Some(x), Some(y)) => x == y,
+        _ => false,
+    }
+}
+
+/// Whether `version` is a prerelease; unparseable versions are not.
+pub fn is_prerelease(version: &str) -> bool {
+    ParsedVersion::parse(version).is_some_and(|parsed| parsed.is_prerelease())
+}
+
+/// The numeric release of `version`, or nothing when it can't be parsed.
+pub fn release(version: &str) -> Vec<u64> {
+    ParsedVersion::parse(version).map(|parsed| parsed.release).unwrap_or_default()
+}
+
+/// Sorts version strings from oldest to newest.
+pub fn sort<T>(items: &mut [T], key: impl Fn(&T) -> &str) {
+    items.sort_by(|a, b| compare(key(a), key(b)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts that each version sorts strictly before the next.
+    fn assert_ascending(versions: &[&str]) {
+        for pair in versions.windows(2) {
+            assert_eq!(compare(pair[0], pair[1]), Ordering::Less, "{} < {}", pair[0], pair[1]);
+            assert_eq!(compare(pair[1], pair[0]), Ordering::Greater, "{} > {}", pair[1], pair[0]);
+        }
+    }
+
+    #[test]
+    fn orders_semver() {
+        assert_ascending(&["1.0.0-alpha", "1.0.0-alpha.1", "1.0.0-beta", "1.0.0-beta.2", "1.0.0-beta.11", "1.0.0-rc.1", "1.0.0"]);
+        assert_ascending(&["1.2.3", "1.2.10", "1.10.0", "2.0.0", "v10.0.0"]);
+    }
+
+    #[test]
+    fn orders_pep440() {
+        assert_ascending(&["3.13.0.dev2", "3.13.0a1", "3.13.0b3", "3.13.0rc1", "3.13.0", "3.13.0.post1", "3.13.1"]);
+        assert_ascending(&["3.12.0", "1!1.0"]);
+    }
+
+    #[test]
+    fn orders_java() {
+        assert_ascending(&["1.8.0_92", "1.8.0_392", "11.0.22", "21-ea+5", "21.0.2+7", "21.0.2+13", "21.0.3+9"]);
+        assert_ascending(&["21.0.2+13", "21.0.2+13-LTS"]);
+    }
+
+    #[test]
+    fn orders_go() {
+        assert_ascending(&["go1.21.6", "go1.22rc1", "go1.22.0", "go1.22.1"]);
+    }
+
+    #[test]
+    fn falls_back_to_natural_order() {
+        assert_eq!(compare("nightly-2", "nightly-10"), Ordering::Less);
+        assert_eq!(compare("latest", "1.0.0"), natural_cmp("latest", "1.0.0"));
+    }
+
+    #[test]
+    fn same_ignores_spelling_differences() {
+        assert!(same("20.11.0", "20.11.0"));
+        assert!(same("v20.11.0", "20.11.0"));
+        assert!(same("go1.22.0", "1.22.0"));
+        assert!(same("1.22", "1.22.0"));
+    }
+
+    #[test]
+    fn same_keeps_distinct_versions_apart() {
+        assert!(!same("20.11.0", "20.11.1"));
+        assert!(!same("3.13.0rc1", "3.13.0"));
+        assert!(!same("21.0.2+13", "21.0.2+7"));
+        assert!(!same("21.0.2+13-LTS", "21.0.2+7-LTS"));
+        assert!(!same("nightly", "stable"));
+    }
+
+    #[test]
+    fn reports_prereleases_and_release_numbers() {
+        assert!(is_prerelease("1.0.0-rc.1"));
+        assert!(is_prerelease("go1.22rc1"));
+        assert!(!is_prerelease("3.12.0.post1"));
+        assert!(!is_prerelease("nightly"));
+        assert_eq!(release("3.13.0rc1"), vec![3, 13, 0]);
+        assert_eq!(release("1.8.0_392"), vec![1, 8, 0, 392]);
+        assert!(release("nightly").is_empty());
+    }
+
+    #[test]
+    fn sorts_oldest_first() {
+        let mut versions = vec!["1.10.0", "1.2.0", "1.2.0-rc.1", "0.9.0"];
+        sort(&mut versions, |v| v);
+        assert_eq!(versions, ["0.9.0", "1.2.0-rc.1", "1.2.0", "1.10.0"]);
+    }
+}